@@ -1,4 +1,4 @@
-use georaster::{geotiff::GeoTiffReader, RasterValue};
+use georaster::{geotiff::GeoTiffReader, RasterValue, SampleType};
 use image::ImageBuffer;
 use std::env;
 use std::fs::File;
@@ -24,7 +24,7 @@ fn main() {
     let mut tiff = GeoTiffReader::open(img_file).expect("Open Tiff");
     let mut img = ImageBuffer::new(w, h);
     for (x, y, pixel) in tiff.pixels(x0, y0, w, h) {
-        if let RasterValue::U16(v) = pixel {
+        if let RasterValue::U16(v) = pixel.cast_to(SampleType::U16) {
             img.put_pixel(x - x0, y - y0, image::Luma([v]));
         }
     }
@@ -0,0 +1,30 @@
+use georaster::geotiff::GeoTiffReader;
+use georaster::pmtiles_writer::PmtilesRasterWriter;
+use georaster::ElevationEncoding;
+use std::env;
+use std::fs::File;
+use std::io::BufReader;
+
+fn main() {
+    let src_fn = env::args().nth(1).expect("Input file required");
+    let dst_fn = env::args().nth(2).expect("Output file required");
+    let max_zoom = env::args()
+        .nth(3)
+        .map(|z| z.parse().expect("Invalid max zoom"))
+        .unwrap_or(10);
+    let encoding = env::args()
+        .nth(4)
+        .map(|e| e.parse::<ElevationEncoding>().expect("Invalid encoding"));
+
+    let img_file = BufReader::new(File::open(src_fn).expect("Open input file"));
+    let mut tiff = GeoTiffReader::open(img_file).expect("Open Tiff");
+    tiff.apply_scale_offset(true);
+
+    let mut writer = PmtilesRasterWriter::new(0, max_zoom).tile_size(512);
+    if let Some(encoding) = encoding {
+        writer = writer.elevation_encoding(encoding);
+    }
+    writer
+        .write(&mut tiff, File::create(dst_fn).expect("Create output file"))
+        .expect("Write PMTiles");
+}
@@ -113,4 +113,15 @@ fn main() {
         .map(|(_x, _y, h)| i64::try_from(h).unwrap_or(0))
         .max();
     assert_eq!(max_height, Some(2161));
+
+    let stats = tiff.statistics(1, true).unwrap();
+    println!(
+        "min: {} max: {} mean: {:.1} stddev: {:.1}",
+        stats.min, stats.max, stats.mean, stats.stddev
+    );
+    let histogram = tiff.histogram(1, 10, Some((0.0, 5000.0)), true).unwrap();
+    for (idx, count) in histogram.counts.iter().enumerate() {
+        let (from, to) = histogram.bin_range(idx);
+        println!("{from:>6}-{to:<6} {count}");
+    }
 }
@@ -26,21 +26,15 @@ fn main() -> std::io::Result<()> {
     let mut ascii_img = vec![b' '; (width * height) as usize];
     for (x, y, pixel) in tiff.pixels(0, 0, width, height) {
         let ascii = match pixel {
-            RasterValue::U8(v) => grey2ascii(v as f32 / u8::MAX as f32),
-            RasterValue::U16(v) => grey2ascii(v as f32 / u16::MAX as f32),
-            RasterValue::U32(v) => grey2ascii(v as f32 / u32::MAX as f32),
-            RasterValue::U64(v) => grey2ascii(v as f32 / u64::MAX as f32),
-            RasterValue::F32(v) => grey2ascii(v / f32::MAX),
-            RasterValue::F64(v) => grey2ascii((v / f64::MAX) as f32),
-            RasterValue::I8(v) => grey2ascii(v as f32 / i8::MAX as f32),
-            RasterValue::I16(v) => grey2ascii(v as f32 / i16::MAX as f32),
-            RasterValue::I32(v) => grey2ascii(v as f32 / i32::MAX as f32),
-            RasterValue::I64(v) => grey2ascii(v as f32 / i64::MAX as f32),
-            RasterValue::Rgb8(r, g, b) => rgb2ascii(r as u16, g as u16, b as u16),
-            RasterValue::Rgb16(r, g, b) => rgb2ascii(r, g, b),
-            RasterValue::Rgba8(r, g, b, _a) => rgb2ascii(r as u16, g as u16, b as u16),
-            RasterValue::Rgba16(r, g, b, _a) => rgb2ascii(r, g, b),
-            _ => ' ',
+            RasterValue::Rgb8(r, g, b) | RasterValue::Rgba8(r, g, b, _) => {
+                rgb2ascii(r as u16, g as u16, b as u16)
+            }
+            RasterValue::Rgb16(r, g, b) | RasterValue::Rgba16(r, g, b, _) => rgb2ascii(r, g, b),
+            _ => pixel
+                .sample_type()
+                .and_then(|t| pixel.normalize(0.0, t.max_value()))
+                .map(|v| grey2ascii(v as f32))
+                .unwrap_or(' '),
         };
         ascii_img[(y * width + x) as usize] = ascii as u8;
     }
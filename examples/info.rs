@@ -5,12 +5,23 @@ use std::io::BufReader;
 
 fn main() {
     let src_fn = env::args().nth(1).expect("Input file required");
+    if src_fn.ends_with(".pmtiles") {
+        pmtiles_info(&src_fn);
+        return;
+    }
     let img_file = BufReader::new(File::open(src_fn).expect("Open input file"));
     let tiff = GeoTiffReader::open(img_file).expect("Open Tiff");
 
     println!("Origin: {:?}", tiff.origin());
     println!("Pixel size: {:?}", tiff.pixel_size());
     println!("SRS: {:?}", tiff.geo_params);
+    println!("NoData: {:?}", tiff.nodata());
+    if let Some(metadata) = tiff.metadata() {
+        println!("Metadata: {:?}", metadata.items);
+        for (idx, band) in metadata.bands.iter().enumerate() {
+            println!("Band #{}: {band:?}", idx + 1);
+        }
+    }
     for (idx, img) in tiff.images().iter().enumerate() {
         println!("Image #{idx}:");
         println!("  Dimensions: {:?}", img.dimensions);
@@ -22,3 +33,35 @@ fn main() {
         println!("  Planar config: {:?}", img.planar_config);
     }
 }
+
+#[cfg(feature = "pmtiles")]
+fn pmtiles_info(src_fn: &str) {
+    use georaster::pmtiles::PmtilesRasterReader;
+
+    let rt = tokio::runtime::Builder::new_current_thread()
+        .build()
+        .unwrap();
+    let pmtiles = rt
+        .block_on(PmtilesRasterReader::open(src_fn))
+        .expect("Open PMTiles");
+    let bounds = pmtiles.bounds();
+    println!("Tile type: {:?}", pmtiles.tile_type());
+    println!("Tile compression: {:?}", pmtiles.tile_compression());
+    println!("Zoom levels: {}-{}", pmtiles.min_zoom(), pmtiles.max_zoom());
+    println!(
+        "Bounds: {} {} {} {}",
+        bounds.left, bounds.bottom, bounds.right, bounds.top
+    );
+    println!(
+        "Center: {:?} (zoom {})",
+        pmtiles.center(),
+        pmtiles.center_zoom()
+    );
+    println!("Elevation encoding: {}", pmtiles.encoding());
+    println!("Metadata: {}", pmtiles.metadata());
+}
+
+#[cfg(not(feature = "pmtiles"))]
+fn pmtiles_info(_src_fn: &str) {
+    eprintln!("PMTiles support requires feature `pmtiles`");
+}
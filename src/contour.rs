@@ -0,0 +1,323 @@
+//! Contour lines of DEM rasters with marching squares.
+
+use crate::Raster;
+use geo_types::{Coord, LineString};
+use std::collections::{HashMap, HashSet};
+
+/// Heights of contour lines
+#[derive(Debug, Clone, PartialEq)]
+pub enum ContourLevels {
+    /// Every multiple of `interval` above or below `base`
+    Interval { base: f64, interval: f64 },
+    /// Explicit heights
+    Fixed(Vec<f64>),
+}
+
+impl ContourLevels {
+    /// Levels within the value range `min`..=`max`
+    fn levels(&self, min: f64, max: f64) -> Vec<f64> {
+        match self {
+            ContourLevels::Interval { base, interval } => {
+                if *interval <= 0.0 || min > max {
+                    return Vec::new();
+                }
+                let k0 = ((min - base) / interval).ceil() as i64;
+                let k1 = ((max - base) / interval).floor() as i64;
+                (k0..=k1).map(|k| base + k as f64 * interval).collect()
+            }
+            ContourLevels::Fixed(levels) => levels
+                .iter()
+                .copied()
+                .filter(|level| (min..=max).contains(level))
+                .collect(),
+        }
+    }
+}
+
+/// Contour line with its height
+#[derive(Debug, Clone, PartialEq)]
+pub struct Contour {
+    pub elevation: f64,
+    /// Line with higher values on the right, closed rings start and end at the same coordinate
+    pub line: LineString,
+}
+
+/// Contour lines of a raster
+///
+/// The contour grid connects pixel centers, heights are read with
+/// `RasterValue::as_f64`. Grid cells with a NoData or NaN corner are skipped,
+/// contour lines end at NoData gaps.
+pub fn contours(raster: &Raster, levels: &ContourLevels) -> Vec<Contour> {
+    let values = raster
+        .values
+        .iter()
+        .map(|v| v.as_f64().filter(|v| !v.is_nan()))
+        .collect::<Vec<_>>();
+    let grid = Grid {
+        values: &values,
+        width: raster.width as usize,
+        height: raster.height as usize,
+        origin: raster.origin,
+        pixel_size: raster.pixel_size,
+        offset: (0, 0),
+    };
+    grid.contours(levels)
+}
+
+/// Pixel centers with heights
+///
+/// Node `(i, j)` is the pixel `offset + (i, j)` of a raster with upper left
+/// corner `origin`. Adjacent windows of the same raster, overlapping by one
+/// pixel, produce identical coordinates along their common border.
+pub(crate) struct Grid<'a> {
+    pub values: &'a [Option<f64>],
+    pub width: usize,
+    pub height: usize,
+    pub origin: [f64; 2],
+    pub pixel_size: [f64; 2],
+    pub offset: (u32, u32),
+}
+
+/// Cell edge from node `(i, j)` to the right (`false`) or downwards (`true`)
+type Edge = (usize, usize, bool);
+
+impl Grid<'_> {
+    pub fn contours(&self, levels: &ContourLevels) -> Vec<Contour> {
+        let (min, max) = self
+            .values
+            .iter()
+            .flatten()
+            .fold((f64::INFINITY, f64::NEG_INFINITY), |(min, max), v| {
+                (min.min(*v), max.max(*v))
+            });
+        levels
+            .levels(min, max)
+            .into_iter()
+            .flat_map(|level| {
+                self.lines(level).into_iter().map(move |line| Contour {
+                    elevation: level,
+                    line,
+                })
+            })
+            .collect()
+    }
+
+    fn value(&self, i: usize, j: usize) -> Option<f64> {
+        self.values[j * self.width + i]
+    }
+
+    /// Oriented segments between cell edges crossing `level`
+    fn segments(&self, level: f64) -> Vec<(Edge, Edge)> {
+        let mut segments = Vec::new();
+        for j in 0..self.height.saturating_sub(1) {
+            for i in 0..self.width.saturating_sub(1) {
+                // Corners clockwise from upper left
+                let nodes = [(i, j), (i + 1, j), (i + 1, j + 1), (i, j + 1)];
+                let Some(heights) = nodes
+                    .iter()
+                    .map(|(i, j)| self.value(*i, *j))
+                    .collect::<Option<Vec<_>>>()
+                else {
+                    continue;
+                };
+                let above = heights.iter().map(|h| *h >= level).collect::<Vec<_>>();
+                // Edge k between corners k and k + 1
+                let edges = [
+                    (i, j, false),
+                    (i + 1, j, true),
+                    (i, j + 1, false),
+                    (i, j, true),
+                ];
+                let crossing = (0..4)
+                    .filter(|k| above[*k] != above[(k + 1) % 4])
+                    .collect::<Vec<_>>();
+                let pairs = match crossing[..] {
+                    [a, b] => vec![(a, b)],
+                    [..] if crossing.len() == 4 => {
+                        // Saddle, disambiguated by the cell center
+                        let center = heights.iter().sum::<f64>() / 4.0 >= level;
+                        if center == above[0] {
+                            vec![(0, 1), (2, 3)]
+                        } else {
+                            vec![(3, 0), (1, 2)]
+                        }
+                    }
+                    _ => continue,
+                };
+                for (a, b) in pairs {
+                    // Higher values on the right in pixel space with y downwards:
+                    // passing from edge k - 1 to edge k leaves corner k on the left,
+                    // passing from edge k to edge k + 2 leaves corner k on the right
+                    let (a, b) = if (a, b) == (0, 3) { (3, 0) } else { (a, b) };
+                    let (from, to) = if b == (a + 1) % 4 {
+                        if above[b] {
+                            (b, a)
+                        } else {
+                            (a, b)
+                        }
+                    } else if above[a] {
+                        (a, b)
+                    } else {
+                        (b, a)
+                    };
+                    segments.push((edges[from], edges[to]));
+                }
+            }
+        }
+        segments
+    }
+
+    /// Crossing of `level` on `edge` as fraction from its first node
+    fn fraction(&self, (i, j, down): Edge, level: f64) -> f64 {
+        let (i2, j2) = if down { (i, j + 1) } else { (i + 1, j) };
+        let (a, b) = (self.value(i, j).unwrap(), self.value(i2, j2).unwrap());
+        if a == b {
+            0.0
+        } else {
+            (level - a) / (b - a)
+        }
+    }
+
+    /// Crossing of `level` on `edge` in raster CRS
+    fn coord(&self, edge: Edge, level: f64) -> Coord {
+        let (i, j, down) = edge;
+        let t = self.fraction(edge, level);
+        // Node positions in the whole raster, independent of the window
+        let x = (i + self.offset.0 as usize) as f64 + if down { 0.0 } else { t };
+        let y = (j + self.offset.1 as usize) as f64 + if down { t } else { 0.0 };
+        Coord {
+            x: self.origin[0] + (x + 0.5) * self.pixel_size[0],
+            y: self.origin[1] + (y + 0.5) * self.pixel_size[1],
+        }
+    }
+
+    /// Segments joined into lines
+    fn lines(&self, level: f64) -> Vec<LineString> {
+        let segments = self.segments(level);
+        let next = segments
+            .iter()
+            .enumerate()
+            .map(|(idx, (start, _))| (*start, idx))
+            .collect::<HashMap<_, _>>();
+        let ends = segments.iter().map(|(_, end)| *end).collect::<HashSet<_>>();
+        let mut used = vec![false; segments.len()];
+        let mut lines = Vec::new();
+        // Open lines first, starting at segments without predecessor
+        let heads = (0..segments.len())
+            .filter(|idx| !ends.contains(&segments[*idx].0))
+            .chain(0..segments.len())
+            .collect::<Vec<_>>();
+        for head in heads {
+            if used[head] {
+                continue;
+            }
+            let mut coords = vec![self.coord(segments[head].0, level)];
+            let mut idx = head;
+            loop {
+                used[idx] = true;
+                let end = segments[idx].1;
+                let coord = self.coord(end, level);
+                if coords.last() != Some(&coord) {
+                    coords.push(coord);
+                }
+                match next.get(&end) {
+                    Some(n) if !used[*n] => idx = *n,
+                    _ => break,
+                }
+            }
+            if coords.len() > 1 {
+                lines.push(LineString::new(coords));
+            }
+        }
+        lines
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::RasterValue;
+
+    /// Cone with height 10 - distance from pixel (2, 2)
+    fn cone() -> Raster {
+        let mut raster = Raster::new(5, 5, [0.0, 5.0], [1.0, -1.0]);
+        for y in 0..5 {
+            for x in 0..5 {
+                let d = (x as f64 - 2.0).hypot(y as f64 - 2.0);
+                raster.set(x, y, RasterValue::F64(10.0 - d));
+            }
+        }
+        raster
+    }
+
+    #[test]
+    fn levels() {
+        let levels = ContourLevels::Interval {
+            base: 5.0,
+            interval: 2.0,
+        };
+        assert_eq!(levels.levels(0.5, 8.0), [1.0, 3.0, 5.0, 7.0]);
+        let levels = ContourLevels::Fixed(vec![1.0, 9.0, 11.0]);
+        assert_eq!(levels.levels(0.5, 10.0), [1.0, 9.0]);
+    }
+
+    #[test]
+    fn cone_rings() {
+        // Level 9 runs through pixel centers
+        let levels = ContourLevels::Fixed(vec![9.5, 9.0, 8.5]);
+        let contours = contours(&cone(), &levels);
+        assert_eq!(contours.len(), 3);
+        assert!(contours.iter().all(|c| c.line.is_closed()));
+        let ring = &contours[0];
+        assert_eq!(ring.elevation, 9.5);
+        assert!(ring.line.is_closed());
+        // Diamond half way between the top and its direct neighbours
+        assert_eq!(ring.line.0.len(), 5);
+        for c in &ring.line.0 {
+            let d = (c.x - 2.5).abs() + (c.y - 2.5).abs();
+            assert!((d - 0.5).abs() < 1e-9, "{c:?}");
+        }
+        // Higher values on the right: clockwise ring in map coordinates
+        let area = ring
+            .line
+            .lines()
+            .map(|l| l.start.x * l.end.y - l.end.x * l.start.y)
+            .sum::<f64>();
+        assert!(area < 0.0);
+    }
+
+    #[test]
+    fn nodata_and_windows() {
+        let mut raster = cone();
+        raster.set(2, 1, RasterValue::NoData);
+        let levels = ContourLevels::Fixed(vec![9.5]);
+        let contours = contours(&raster, &levels);
+        // Ring opened at the NoData pixel
+        assert_eq!(contours.len(), 1);
+        assert!(!contours[0].line.is_closed());
+        assert_eq!(contours[0].line.0.len(), 3);
+
+        // Left and right window overlapping by one pixel meet at the same coordinates
+        let values = cone().values.iter().map(|v| v.as_f64()).collect::<Vec<_>>();
+        let window = |x0: usize, x1: usize| {
+            let values = (0..5)
+                .flat_map(|y| (x0..x1).map(move |x| (x, y)))
+                .map(|(x, y)| values[y * 5 + x])
+                .collect::<Vec<_>>();
+            let grid = Grid {
+                values: &values,
+                width: x1 - x0,
+                height: 5,
+                origin: [0.0, 5.0],
+                pixel_size: [1.0, -1.0],
+                offset: (x0 as u32, 0),
+            };
+            grid.contours(&levels)
+        };
+        let (left, right) = (window(0, 3), window(2, 5));
+        assert_eq!((left.len(), right.len()), (1, 1));
+        let (left, right) = (&left[0].line.0, &right[0].line.0);
+        assert_eq!(left.first(), right.last());
+        assert_eq!(left.last(), right.first());
+    }
+}
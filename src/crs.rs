@@ -0,0 +1,183 @@
+//! Coordinate transformations between CRS with `geodesy`.
+
+use crate::{Coordinate, GeorasterError, GeorasterResult};
+use geodesy::prelude::*;
+
+/// Geodesy operator projecting WGS84 longitude/latitude into CRS `epsg`
+///
+/// Returns `Some("")` for WGS84 and `None` for unsupported CRS.
+fn projection(epsg: u32) -> Option<String> {
+    // Datum shift from WGS84 into a datum with Helmert translations
+    let datum = |ellps: &str, x: f64, y: f64, z: f64| {
+        format!("cart ellps=WGS84 | helmert x={x} y={y} z={z} | cart inv ellps={ellps} | ")
+    };
+    let definition = match epsg {
+        4326 => String::new(),
+        3857 | 900913 => "webmerc".to_string(),
+        // WGS 84 / UTM
+        32601..=32660 => format!("utm zone={} ellps=WGS84", epsg - 32600),
+        32701..=32760 => format!("utm zone={} south ellps=WGS84", epsg - 32700),
+        // ETRS89 / UTM
+        25828..=25838 => format!("utm zone={} ellps=GRS80", epsg - 25800),
+        // NAD27 / UTM (CONUS mean translation)
+        26703..=26722 => {
+            datum("clrk66", 8.0, -160.0, -176.0)
+                + &format!("utm zone={} ellps=clrk66", epsg - 26700)
+        }
+        // ETRS89-extended / LAEA Europe
+        3035 => "laea ellps=GRS80 lat_0=52 lon_0=10 x_0=4321000 y_0=3210000".to_string(),
+        // CH1903+ / LV95 and CH1903 / LV03
+        2056 | 21781 => {
+            let (x_0, y_0) = if epsg == 2056 {
+                (2600000, 1200000)
+            } else {
+                (600000, 200000)
+            };
+            datum("bessel", -674.374, -15.056, -405.346)
+                + &format!(
+                    "somerc ellps=bessel lat_0=46.9524055555556 lon_0=7.43958333333333 \
+                     x_0={x_0} y_0={y_0}"
+                )
+        }
+        _ => return None,
+    };
+    Some(definition)
+}
+
+/// Coordinate transformation between two CRS given by EPSG code
+///
+/// Supported are WGS84 (4326), WebMercator (3857), UTM zones in WGS84,
+/// ETRS89 and NAD27, LAEA Europe (3035) and Swiss LV95 (2056) and LV03 (21781).
+/// Geographic coordinates are longitude/latitude in degrees.
+pub struct Transformer {
+    ctx: Minimal,
+    /// Projection of source CRS, `None` for WGS84
+    src: Option<OpHandle>,
+    /// Projection of destination CRS, `None` for WGS84
+    dst: Option<OpHandle>,
+}
+
+impl Transformer {
+    pub fn new(src_epsg: u32, dst_epsg: u32) -> GeorasterResult<Self> {
+        let mut ctx = Minimal::new();
+        let mut op = |epsg: u32| -> GeorasterResult<Option<OpHandle>> {
+            let definition = projection(epsg)
+                .ok_or_else(|| GeorasterError::UnsupportedCrs(format!("EPSG:{epsg}")))?;
+            if definition.is_empty() || src_epsg == dst_epsg {
+                return Ok(None);
+            }
+            Ok(Some(ctx.op(&definition)?))
+        };
+        let src = op(src_epsg)?;
+        let dst = op(dst_epsg)?;
+        Ok(Transformer { ctx, src, dst })
+    }
+
+    /// Transform coordinate from source into destination CRS
+    pub fn transform(&self, coord: impl Into<Coordinate>) -> GeorasterResult<Coordinate> {
+        let coord = coord.into();
+        let mut data = [match self.src {
+            Some(_) => Coor4D::raw(coord.x, coord.y, 0.0, 0.0),
+            None => Coor4D::geo(coord.y, coord.x, 0.0, 0.0),
+        }];
+        if let Some(src) = self.src {
+            data[0] = self.inverse(src, data[0])?;
+        }
+        if let Some(dst) = self.dst {
+            self.apply(dst, Fwd, &mut data)?;
+            return Ok(Coordinate {
+                x: data[0][0],
+                y: data[0][1],
+            });
+        }
+        Ok(Coordinate {
+            x: data[0][0].to_degrees(),
+            y: data[0][1].to_degrees(),
+        })
+    }
+
+    /// Inverse projection refined with Newton iterations of the forward projection
+    ///
+    /// The inverse of some `geodesy` operators (e.g. `somerc`) is not exact.
+    fn inverse(&self, op: OpHandle, xy: Coor4D) -> GeorasterResult<Coor4D> {
+        const STEP: f64 = 1e-7;
+        let mut geo = [xy];
+        self.apply(op, Inv, &mut geo)?;
+        let fwd = |lon: f64, lat: f64| -> GeorasterResult<(f64, f64)> {
+            let mut data = [Coor4D::raw(lon, lat, 0.0, 0.0)];
+            self.apply(op, Fwd, &mut data)?;
+            Ok((data[0][0] - xy[0], data[0][1] - xy[1]))
+        };
+        let (mut lon, mut lat) = (geo[0][0], geo[0][1]);
+        for _ in 0..10 {
+            let (dx, dy) = fwd(lon, lat)?;
+            if dx.abs() < 1e-6 && dy.abs() < 1e-6 {
+                break;
+            }
+            // Numerical Jacobian
+            let (dx_lon, dy_lon) = fwd(lon + STEP, lat)?;
+            let (dx_lat, dy_lat) = fwd(lon, lat + STEP)?;
+            let (a, b) = ((dx_lon - dx) / STEP, (dx_lat - dx) / STEP);
+            let (c, d) = ((dy_lon - dy) / STEP, (dy_lat - dy) / STEP);
+            let det = a * d - b * c;
+            if det == 0.0 {
+                break;
+            }
+            lon -= (d * dx - b * dy) / det;
+            lat -= (a * dy - c * dx) / det;
+        }
+        geo[0][0] = lon;
+        geo[0][1] = lat;
+        Ok(geo[0])
+    }
+
+    fn apply(
+        &self,
+        op: OpHandle,
+        direction: Direction,
+        data: &mut [Coor4D; 1],
+    ) -> GeorasterResult<()> {
+        let transformed = self.ctx.apply(op, direction, data)?;
+        if transformed != data.len() || data[0][0].is_nan() {
+            return Err(GeorasterError::Geodesy(geodesy::Error::General(
+                "Coordinate transformation failed",
+            )));
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn assert_close(coord: Coordinate, x: f64, y: f64, tolerance: f64) {
+        assert!(
+            (coord.x - x).abs() < tolerance && (coord.y - y).abs() < tolerance,
+            "{coord:?} <> ({x}, {y})"
+        );
+    }
+
+    #[test]
+    fn projections() {
+        let t = Transformer::new(4326, 32632).unwrap();
+        assert_close(t.transform((9.0, 0.0)).unwrap(), 500000.0, 0.0, 1e-6);
+
+        let t = Transformer::new(4326, 3035).unwrap();
+        let xy = t.transform((10.0, 52.0)).unwrap();
+        assert_close(xy, 4321000.0, 3210000.0, 1e-6);
+        let t = Transformer::new(3035, 4326).unwrap();
+        assert_close(t.transform(xy).unwrap(), 10.0, 52.0, 1e-9);
+
+        // Old observatory Bern
+        let t = Transformer::new(4326, 2056).unwrap();
+        let xy = t.transform((7.438632495, 46.951082877)).unwrap();
+        assert_close(xy, 2600000.0, 1200000.0, 2.0);
+        let t = Transformer::new(2056, 4326).unwrap();
+        assert_close(t.transform(xy).unwrap(), 7.438632495, 46.951082877, 1e-8);
+        let t = Transformer::new(2056, 21781).unwrap();
+        assert_close(t.transform(xy).unwrap(), 600000.0, 200000.0, 2.0);
+
+        assert!(Transformer::new(4326, 31467).is_err());
+    }
+}
@@ -1,5 +1,41 @@
 //! Common geo types
 
+/// Mean earth radius in metres
+pub(crate) const EARTH_RADIUS: f64 = 6_371_008.8;
+
+/// Semi-major axis of the WebMercator sphere
+pub(crate) const WEB_MERCATOR_RADIUS: f64 = 6_378_137.0;
+
+/// Ground metres per CRS unit in x and y direction at CRS y coordinate `y`
+///
+/// Geographic WGS84 (4326) and WebMercator (3857) are scaled,
+/// other CRS are expected to have units in metres.
+pub(crate) fn ground_scale(epsg: Option<u32>, y: f64) -> (f64, f64) {
+    match epsg {
+        Some(4326) => {
+            let metres = EARTH_RADIUS.to_radians();
+            (metres * y.to_radians().cos(), metres)
+        }
+        Some(3857) | Some(900913) => {
+            // Scale factor of the Mercator projection
+            let lat = (y / WEB_MERCATOR_RADIUS).sinh().atan();
+            (lat.cos(), lat.cos())
+        }
+        _ => (1.0, 1.0),
+    }
+}
+
+/// CRS for conversion of distances into metres on the ground
+///
+/// Geographic CRS are converted like geographic WGS84 (4326).
+pub(crate) fn ground_epsg(epsg: Option<u32>, geographic: bool) -> Option<u32> {
+    if geographic {
+        Some(4326)
+    } else {
+        epsg
+    }
+}
+
 /// 2D coordinate representation
 ///
 /// Coordinates can be converted from and to `geo` by activating
@@ -61,10 +97,150 @@ impl From<[f64; 2]> for Coordinate {
     }
 }
 
+/// Axis aligned bounding box
+///
+/// The CRS of the coordinates is given by an optional EPSG code. Without
+/// EPSG code, coordinates are in the CRS of the raster or API the bounding box
+/// is used with. Raster bounds are returned with the EPSG code of the raster,
+/// if known. Geometric operations keep the EPSG code of `self` and assume both
+/// bounding boxes in the same CRS. Bounding boxes can be converted from and to
+/// `geo_types::Rect` by activating the `geo` feature and reprojected with
+/// the `geodesy` feature.
+///
+/// ```rust
+/// use georaster::BoundingBox;
+///
+/// let bbox = BoundingBox::new(7.0, 46.0, 8.0, 47.0);
+/// let other = BoundingBox::new(7.5, 45.0, 9.0, 46.5);
+/// assert_eq!(
+///     bbox.intersection(&other),
+///     Some(BoundingBox::new(7.5, 46.0, 8.0, 46.5))
+/// );
+/// assert_eq!(bbox.union(&other), BoundingBox::new(7.0, 45.0, 9.0, 47.0));
+/// assert!(bbox.contains((7.5, 46.5)));
+/// assert_eq!(bbox.buffer(0.5), BoundingBox::new(6.5, 45.5, 8.5, 47.5));
+/// ```
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct BoundingBox {
+    pub left: f64,
+    pub bottom: f64,
+    pub right: f64,
+    pub top: f64,
+    /// EPSG code of the CRS
+    pub epsg: Option<u32>,
+}
+
+impl BoundingBox {
+    /// Create a new bounding box from its min and max coordinates without CRS
+    pub fn new(left: f64, bottom: f64, right: f64, top: f64) -> Self {
+        BoundingBox {
+            left,
+            bottom,
+            right,
+            top,
+            epsg: None,
+        }
+    }
+
+    /// Set EPSG code of the CRS
+    pub fn with_epsg(mut self, epsg: u32) -> Self {
+        self.epsg = Some(epsg);
+        self
+    }
+
+    /// Bounding box of all coordinates
+    ///
+    /// Returns the `None` variant for an empty iterator.
+    pub fn from_coords<C: Into<Coordinate>>(coords: impl IntoIterator<Item = C>) -> Option<Self> {
+        coords.into_iter().fold(None, |bbox, c| {
+            let c = c.into();
+            Some(match bbox {
+                None => BoundingBox::new(c.x, c.y, c.x, c.y),
+                Some(bbox) => BoundingBox::new(
+                    bbox.left.min(c.x),
+                    bbox.bottom.min(c.y),
+                    bbox.right.max(c.x),
+                    bbox.top.max(c.y),
+                ),
+            })
+        })
+    }
+
+    pub fn width(&self) -> f64 {
+        self.right - self.left
+    }
+
+    pub fn height(&self) -> f64 {
+        self.top - self.bottom
+    }
+
+    pub fn center(&self) -> Coordinate {
+        Coordinate {
+            x: (self.left + self.right) / 2.0,
+            y: (self.bottom + self.top) / 2.0,
+        }
+    }
+
+    /// Common area of both bounding boxes
+    ///
+    /// Returns the `None` variant for disjoint bounding boxes.
+    pub fn intersection(&self, other: &BoundingBox) -> Option<BoundingBox> {
+        let bbox = BoundingBox {
+            left: self.left.max(other.left),
+            bottom: self.bottom.max(other.bottom),
+            right: self.right.min(other.right),
+            top: self.top.min(other.top),
+            epsg: self.epsg,
+        };
+        (bbox.left <= bbox.right && bbox.bottom <= bbox.top).then_some(bbox)
+    }
+
+    /// Bounding box containing both bounding boxes
+    pub fn union(&self, other: &BoundingBox) -> BoundingBox {
+        BoundingBox {
+            left: self.left.min(other.left),
+            bottom: self.bottom.min(other.bottom),
+            right: self.right.max(other.right),
+            top: self.top.max(other.top),
+            epsg: self.epsg,
+        }
+    }
+
+    /// Check whether bounding boxes overlap or touch
+    pub fn intersects(&self, other: &BoundingBox) -> bool {
+        self.intersection(other).is_some()
+    }
+
+    /// Check whether `coord` is inside or on the boundary
+    pub fn contains(&self, coord: impl Into<Coordinate>) -> bool {
+        let coord = coord.into();
+        (self.left..=self.right).contains(&coord.x) && (self.bottom..=self.top).contains(&coord.y)
+    }
+
+    /// Check whether `other` is completely inside
+    pub fn contains_bbox(&self, other: &BoundingBox) -> bool {
+        self.contains((other.left, other.bottom)) && self.contains((other.right, other.top))
+    }
+
+    /// Bounding box enlarged by `distance` on all sides, shrinked for negative values
+    pub fn buffer(&self, distance: f64) -> BoundingBox {
+        BoundingBox {
+            left: self.left - distance,
+            bottom: self.bottom - distance,
+            right: self.right + distance,
+            top: self.top + distance,
+            epsg: self.epsg,
+        }
+    }
+}
+
+#[cfg(feature = "geo")]
+pub(crate) use geo_coords::polygon_spans;
+
 #[cfg(feature = "geo")]
 mod geo_coords {
-    use super::Coordinate;
-    use geo_types::{coord, Coord};
+    use super::{BoundingBox, Coordinate};
+    use geo_types::{coord, Coord, Polygon, Rect};
 
     impl From<Coordinate> for Coord {
         fn from(val: Coordinate) -> Self {
@@ -81,7 +257,62 @@ mod geo_coords {
         }
     }
 
+    impl From<BoundingBox> for Rect {
+        fn from(bbox: BoundingBox) -> Self {
+            Rect::new(
+                coord! { x: bbox.left, y: bbox.bottom },
+                coord! { x: bbox.right, y: bbox.top },
+            )
+        }
+    }
+
+    impl From<Rect> for BoundingBox {
+        fn from(rect: Rect) -> BoundingBox {
+            BoundingBox::new(rect.min().x, rect.min().y, rect.max().x, rect.max().y)
+        }
+    }
+
+    /// Pixel spans `(row, start_col, end_col)` of pixels with center inside `polygons`
+    ///
+    /// The grid has its upper left corner at `origin`. Spans are limited to the pixel
+    /// window `(x0, y0, x1, y1)`, end columns are exclusive. Polygons are filled with
+    /// the even-odd rule, so pixels in holes are excluded.
+    pub(crate) fn polygon_spans(
+        polygons: &[Polygon],
+        origin: [f64; 2],
+        pixel_size: [f64; 2],
+        window: (u32, u32, u32, u32),
+    ) -> Vec<(u32, u32, u32)> {
+        let (x0, y0, x1, y1) = window;
+        let mut spans = Vec::new();
+        let mut crossings = Vec::new();
+        for row in y0..y1 {
+            let y = origin[1] + (row as f64 + 0.5) * pixel_size[1];
+            crossings.clear();
+            let rings = polygons
+                .iter()
+                .flat_map(|p| std::iter::once(p.exterior()).chain(p.interiors()));
+            for line in rings.flat_map(|ring| ring.lines()) {
+                let (p, q) = (line.start, line.end);
+                if (p.y <= y) != (q.y <= y) {
+                    let x = p.x + (y - p.y) * (q.x - p.x) / (q.y - p.y);
+                    crossings.push((x - origin[0]) / pixel_size[0]);
+                }
+            }
+            crossings.sort_by(f64::total_cmp);
+            for pair in crossings.chunks_exact(2) {
+                let start = (pair[0] - 0.5).ceil().clamp(x0 as f64, x1 as f64) as u32;
+                let end = (pair[1] - 0.5).ceil().clamp(x0 as f64, x1 as f64) as u32;
+                if start < end {
+                    spans.push((row, start, end));
+                }
+            }
+        }
+        spans
+    }
+
     #[test]
+    #[allow(clippy::clone_on_copy)]
     fn geo_conversion() {
         let coord = coord! { x: 1.2345, y: 6.7890 };
         let coordinate: Coordinate = coord.clone().into();
@@ -97,14 +328,93 @@ mod geo_coords {
 
         assert_eq!(coord.x, coordinate.x);
         assert_eq!(coord.y, coordinate.y);
+
+        let rect: Rect = BoundingBox::new(7.0, 47.0, 8.0, 46.0).into();
+        assert_eq!(rect.min(), coord! { x: 7.0, y: 46.0 });
+        let bbox: BoundingBox = rect.into();
+        assert_eq!(bbox, BoundingBox::new(7.0, 46.0, 8.0, 47.0));
+    }
+
+    #[test]
+    fn spans() {
+        use geo_types::{polygon, LineString};
+
+        // Diamond on a 6x6 grid with a square hole
+        let mut diamond = polygon![
+            (x: 3.0, y: 0.0), (x: 6.0, y: 3.0), (x: 3.0, y: 6.0), (x: 0.0, y: 3.0)
+        ];
+        let spans = polygon_spans(&[diamond.clone()], [0.0, 6.0], [1.0, -1.0], (0, 0, 6, 6));
+        // Centers on the right boundary are outside
+        assert_eq!(
+            spans,
+            [
+                (0, 2, 3),
+                (1, 1, 4),
+                (2, 0, 5),
+                (3, 0, 5),
+                (4, 1, 4),
+                (5, 2, 3)
+            ]
+        );
+        diamond.interiors_push(LineString::from(vec![
+            (2.0, 2.0),
+            (4.0, 2.0),
+            (4.0, 4.0),
+            (2.0, 4.0),
+        ]));
+        let spans = polygon_spans(&[diamond], [0.0, 6.0], [1.0, -1.0], (1, 0, 6, 3));
+        assert_eq!(spans, [(0, 2, 3), (1, 1, 4), (2, 1, 2), (2, 4, 5)]);
     }
 }
 
 #[cfg(feature = "geodesy")]
 mod geodesy_coords {
-    use super::Coordinate;
+    use super::{BoundingBox, Coordinate};
+    use crate::crs::Transformer;
+    use crate::{GeorasterError, GeorasterResult};
     use geodesy::{Coor2D, CoordinateTuple};
 
+    impl BoundingBox {
+        /// Reproject into CRS `dst_epsg`
+        ///
+        /// Each edge is densified with 21 points. Returns an error for
+        /// bounding boxes without EPSG code.
+        pub fn to_crs(&self, dst_epsg: u32) -> GeorasterResult<BoundingBox> {
+            let src_epsg = self
+                .epsg
+                .ok_or_else(|| GeorasterError::UnsupportedCrs("unknown".to_string()))?;
+            let bbox = self.transform(&Transformer::new(src_epsg, dst_epsg)?, 21)?;
+            Ok(bbox.with_epsg(dst_epsg))
+        }
+
+        /// Bounding box of the transformed boundary with `densify` points per edge
+        ///
+        /// Densification catches curved edges in the destination CRS. The
+        /// returned bounding box has no EPSG code.
+        pub fn transform(
+            &self,
+            transformer: &Transformer,
+            densify: u32,
+        ) -> GeorasterResult<BoundingBox> {
+            let n = densify.max(2);
+            let points = (0..n).flat_map(|i| {
+                let t = i as f64 / (n - 1) as f64;
+                let x = self.left + t * self.width();
+                let y = self.bottom + t * self.height();
+                [
+                    (x, self.bottom),
+                    (x, self.top),
+                    (self.left, y),
+                    (self.right, y),
+                ]
+            });
+            let coords = points
+                .map(|p| transformer.transform(p))
+                .collect::<GeorasterResult<Vec<_>>>()?;
+            Ok(BoundingBox::from_coords(coords).unwrap_or_default())
+        }
+    }
+
     impl From<Coordinate> for Coor2D {
         fn from(val: Coordinate) -> Self {
             Coor2D::raw(val.x, val.y)
@@ -121,6 +431,7 @@ mod geodesy_coords {
     }
 
     #[test]
+    #[allow(clippy::clone_on_copy)]
     fn geodesy_conversion() {
         let coor2d = Coor2D::geo(1.2345, 6.7890);
         let coordinate: Coordinate = coor2d.clone().into();
@@ -137,4 +448,25 @@ mod geodesy_coords {
         assert_eq!(coor2d.x(), coordinate.x);
         assert_eq!(coor2d.y(), coordinate.y);
     }
+
+    #[test]
+    fn bbox_reprojection() {
+        let bbox = BoundingBox::new(6.0, 46.0, 12.0, 48.0).with_epsg(4326);
+        let utm = bbox.to_crs(32632).unwrap();
+        assert_eq!(utm.epsg, Some(32632));
+        let t = Transformer::new(4326, 32632).unwrap();
+        // Southernmost point is in the middle of the bottom edge
+        let bottom = t.transform((9.0, 46.0)).unwrap();
+        assert!((utm.bottom - bottom.y).abs() < 1e-6);
+        let corners = bbox.transform(&t, 2).unwrap();
+        assert!(corners.bottom > utm.bottom);
+        assert!(utm.contains_bbox(&corners));
+
+        let wgs84 = utm.to_crs(4326).unwrap();
+        assert_eq!(wgs84.epsg, Some(4326));
+        assert!(wgs84.contains_bbox(&bbox));
+        assert!(bbox.to_crs(31467).is_err());
+        // Unknown source CRS
+        assert!(corners.to_crs(4326).is_err());
+    }
 }
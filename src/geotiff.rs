@@ -4,15 +4,40 @@
 // GDAL TIFF driver: https://gdal.org/drivers/raster/gtiff.html
 // GDAL COG driver: https://gdal.org/drivers/raster/cog.html
 
-use crate::{GeorasterResult, RasterValue};
-use std::io::{Read, Seek};
-use tiff::decoder::{ifd, Decoder, DecodingResult};
-use tiff::tags::{PhotometricInterpretation, PlanarConfiguration, Tag};
-use tiff::{TiffError, TiffResult};
+#[cfg(feature = "geo")]
+use crate::contour::{self, Contour, ContourLevels};
+#[cfg(feature = "geodesy")]
+use crate::crs::Transformer;
+use crate::geo::{self, BoundingBox};
+use crate::metadata::GdalMetadata;
+#[cfg(feature = "geo")]
+use crate::polygonize::{self, Connectivity, Region};
+use crate::profile::{self, Profile, ProfilePoint, ProfileStep};
+use crate::raster::{check_window_size, pixel_window, GeoRaster, Raster};
+#[cfg(feature = "geo")]
+use crate::stats::ZonalStatistics;
+use crate::stats::{Histogram, Statistics, StatisticsBuilder};
+use crate::viewshed::{self, LineOfSight, Observer};
+use crate::{GeorasterError, GeorasterResult, RasterValue};
+#[cfg(feature = "geo")]
+use geo_types::{MultiPolygon, Polygon};
+use std::collections::HashMap;
+use std::io::{Read, Seek, SeekFrom};
+use tiff::decoder::{ifd, ChunkType, Decoder, DecodingBuffer, DecodingResult};
+use tiff::tags::{
+    CompressionMethod, PhotometricInterpretation, PlanarConfiguration, SampleFormat, Tag,
+};
+use tiff::{TiffError, TiffFormatError, TiffResult, TiffUnsupportedError};
 
 use crate::Coordinate;
 
+/// Maximal number of decoded mask chunks kept in memory
+const MASK_CACHE_SIZE: usize = 64;
+
 /// GeoTIFF file reader
+///
+/// Paletted images are read as color indices, which is supported for 8 bit
+/// images without compression or with LZW or Deflate compression.
 pub struct GeoTiffReader<R: Read + Seek> {
     decoder: Decoder<R>,
     band_idx: u8,
@@ -24,6 +49,21 @@ pub struct GeoTiffReader<R: Read + Seek> {
     pixel_scale: Option<Vec<f64>>,
     model_transformation: Option<Vec<f64>>,
     tie_points: Option<Vec<f64>>,
+    /// Byte order of the TIFF file
+    big_endian: bool,
+    nodata: Option<f64>,
+    apply_scale_offset: bool,
+    /// Internal mask of each image
+    masks: Vec<Option<Mask>>,
+    /// Decoded mask chunks by mask image and chunk index
+    mask_chunks: HashMap<(usize, u32), DecodingResult>,
+}
+
+/// Internal transparency mask of an image
+struct Mask {
+    /// Index of the mask image
+    index: usize,
+    dims: TileAttributes,
 }
 
 /// Image information from TIFF IFD
@@ -37,11 +77,28 @@ pub struct ImageInfo {
     // https://awaresystems.be/imaging/tiff/tifftags/planarconfiguration.html
     pub planar_config: Option<PlanarConfiguration>,
     pub samples: u8,
+    // https://awaresystems.be/imaging/tiff/tifftags/sampleformat.html
+    pub sample_format: Option<SampleFormat>,
+    // https://awaresystems.be/imaging/tiff/tifftags/newsubfiletype.html
+    pub subfile_type: Option<u32>,
+    /// Content of `GDAL_METADATA` tag
+    pub gdal_metadata: Option<GdalMetadata>,
+}
+
+/// Complex sample types (SampleFormat 5/6), which are not decoded by `tiff`
+#[derive(Clone, Copy, Debug)]
+enum ComplexType {
+    CI16,
+    CI32,
+    CF32,
 }
 
 impl<R: Read + Seek + Send> GeoTiffReader<R> {
     /// Open GeoTIFF and read header information
-    pub fn open(src: R) -> GeorasterResult<Self> {
+    pub fn open(mut src: R) -> GeorasterResult<Self> {
+        let mut byte_order = [0; 2];
+        src.read_exact(&mut byte_order)?;
+        src.seek(SeekFrom::Start(0))?;
         let mut decoder = Decoder::new(src)?;
 
         // Read GeoTIFF tags
@@ -51,12 +108,21 @@ impl<R: Read + Seek + Send> GeoTiffReader<R> {
         let model_transformation = decoder.get_tag_f64_vec(Tag::ModelTransformationTag).ok();
         let tie_points = decoder.get_tag_f64_vec(Tag::ModelTiepointTag).ok();
         let _geo_double_params = decoder.get_tag_f64_vec(Tag::GeoDoubleParamsTag).ok();
-        let _nodata = decoder.get_tag_ascii_string(Tag::GdalNodata).ok();
+        let nodata = decoder
+            .get_tag_ascii_string(Tag::GdalNodata)
+            .ok()
+            .and_then(|s| {
+                s.trim_matches(|c: char| c == '\0' || c.is_whitespace())
+                    .parse()
+                    .ok()
+            });
 
         // Read all IFDs
         let mut images = Vec::new();
+        let mut chunk_dims = Vec::new();
         loop {
             images.push(ImageInfo::decode(&mut decoder));
+            chunk_dims.push(decoder.chunk_dimensions());
             if decoder.more_images() {
                 decoder.next_image().expect("Read image info")
             } else {
@@ -64,6 +130,21 @@ impl<R: Read + Seek + Send> GeoTiffReader<R> {
             }
         }
         let cur_image_idx = images.len() - 1;
+        let masks = images
+            .iter()
+            .enumerate()
+            .map(|(idx, img)| {
+                let index = images.iter().enumerate().position(|(mask_idx, mask)| {
+                    mask_idx != idx && mask.is_mask() && mask.dimensions == img.dimensions
+                })?;
+                let dims = TileAttributes::from_dims(
+                    images[index].dimensions.unwrap_or((0, 0)),
+                    chunk_dims[index],
+                    images[index].planar_config,
+                );
+                Some(Mask { index, dims })
+            })
+            .collect();
 
         let reader = GeoTiffReader {
             decoder,
@@ -75,6 +156,11 @@ impl<R: Read + Seek + Send> GeoTiffReader<R> {
             pixel_scale,
             model_transformation,
             tie_points,
+            big_endian: &byte_order == b"MM",
+            nodata,
+            apply_scale_offset: false,
+            masks,
+            mask_chunks: HashMap::new(),
         };
 
         Ok(reader)
@@ -90,6 +176,11 @@ impl<R: Read + Seek + Send> GeoTiffReader<R> {
         &self.images[self.cur_image_idx]
     }
 
+    /// Index of current image
+    pub fn image_index(&self) -> usize {
+        self.cur_image_idx
+    }
+
     /// Load image info into reader
     pub fn seek_to_image(&mut self, index: usize) -> GeorasterResult<()> {
         self.decoder.seek_to_image(index)?;
@@ -97,6 +188,53 @@ impl<R: Read + Seek + Send> GeoTiffReader<R> {
         Ok(())
     }
 
+    /// Image index and dimensions of the overview with the lowest resolution,
+    /// which is at least `factor` times finer than the full resolution image
+    pub(crate) fn best_image(&self, factor: f64) -> (usize, (u32, u32)) {
+        let images = &self.images;
+        let (width, height) = images[0].dimensions.unwrap_or((0, 0));
+        images
+            .iter()
+            .enumerate()
+            .filter(|(_, img)| !img.is_mask() && img.samples == images[0].samples)
+            .filter_map(|(idx, img)| Some((idx, img.dimensions?)))
+            .filter(|(_, (w, _))| *w > 0 && width as f64 / *w as f64 <= factor.max(1.0))
+            .min_by_key(|(_, (w, h))| *w as u64 * *h as u64)
+            .unwrap_or((0, (width, height)))
+    }
+
+    /// GDAL metadata of the dataset
+    pub fn metadata(&self) -> Option<&GdalMetadata> {
+        self.images.first()?.gdal_metadata.as_ref()
+    }
+
+    /// NoData value from `GDAL_NODATA` tag
+    pub fn nodata(&self) -> Option<f64> {
+        self.nodata
+    }
+
+    /// Return physical values in `read_pixel` and `pixels`
+    ///
+    /// Applies scale and offset of the selected band from the GDAL metadata
+    /// and returns `RasterValue::NoData` for NoData values.
+    pub fn apply_scale_offset(&mut self, apply: bool) {
+        self.apply_scale_offset = apply;
+    }
+
+    fn value_transform(&self) -> Option<ValueTransform> {
+        if !self.apply_scale_offset {
+            return None;
+        }
+        let scale_offset = self
+            .metadata()
+            .and_then(|md| md.band(self.band_idx + 1))
+            .and_then(|band| band.scale_offset());
+        Some(ValueTransform {
+            scale_offset,
+            nodata: self.nodata,
+        })
+    }
+
     pub fn origin(&self) -> Option<[f64; 2]> {
         match &self.tie_points {
             Some(tie_points) if tie_points.len() == 6 => Some([tie_points[3], tie_points[4]]),
@@ -104,6 +242,7 @@ impl<R: Read + Seek + Send> GeoTiffReader<R> {
         }
     }
 
+    /// Pixel size of the full resolution image
     pub fn pixel_size(&self) -> Option<[f64; 2]> {
         match &self.pixel_scale {
             Some(mps) => Some([mps[0], -mps[1]]),
@@ -111,6 +250,53 @@ impl<R: Read + Seek + Send> GeoTiffReader<R> {
         }
     }
 
+    /// Pixel size of the current image, scaled for overviews
+    fn image_pixel_size(&self) -> Option<[f64; 2]> {
+        let [pixel_size_x, pixel_size_y] = self.pixel_size()?;
+        let full = self.images.first().and_then(|img| img.dimensions);
+        match (full, self.image_info().dimensions) {
+            (Some((full_width, full_height)), Some((width, height))) if width > 0 && height > 0 => {
+                Some([
+                    pixel_size_x * full_width as f64 / width as f64,
+                    pixel_size_y * full_height as f64 / height as f64,
+                ])
+            }
+            _ => Some([pixel_size_x, pixel_size_y]),
+        }
+    }
+
+    /// EPSG code of the CRS from GeoKeys
+    ///
+    /// Returns `None` for user-defined or missing CRS definitions.
+    pub fn epsg_code(&self) -> Option<u32> {
+        let code = if self.is_geographic() {
+            // GeographicTypeGeoKey
+            self.geo_key_value(2048)
+        } else {
+            // ProjectedCSTypeGeoKey
+            self.geo_key_value(3072)
+                .or_else(|| self.geo_key_value(2048))
+        }?;
+        (code > 0 && code != 32767).then_some(code)
+    }
+
+    /// Check whether the raster has a geographic CRS with coordinates in degrees
+    pub fn is_geographic(&self) -> bool {
+        // GTModelTypeGeoKey
+        self.geo_key_value(1024) == Some(2)
+    }
+
+    /// Value of a GeoKey stored in the GeoKeyDirectoryTag
+    fn geo_key_value(&self, key_id: u32) -> Option<u32> {
+        // http://docs.opengeospatial.org/is/19-008r4/19-008r4.html#_requirements_class_geokeydirectorytag
+        self.geo_keys
+            .as_ref()?
+            .get(4..)?
+            .chunks_exact(4)
+            .find(|entry| entry[0] == key_id && entry[1] == 0)
+            .map(|entry| entry[3])
+    }
+
     pub fn select_raster_band(&mut self, band: u8) -> TiffResult<()> {
         if band < 1 || band > self.num_bands() {
             return Err(TiffError::LimitsExceeded);
@@ -136,6 +322,10 @@ impl<R: Read + Seek + Send> GeoTiffReader<R> {
 
     /// Samples per pixel
     fn spp(&mut self) -> u8 {
+        if self.image_info().complex_type().is_some() {
+            // Real and imaginary part
+            return 2;
+        }
         match self.image_info().planar_config {
             Some(PlanarConfiguration::Planar) => 1,
             _ => {
@@ -171,8 +361,96 @@ impl<R: Read + Seek + Send> GeoTiffReader<R> {
         let chunk_index = tiles.get_chunk_index(x, y, self.band_idx);
         let spp = self.spp();
         let offset = tiles.get_chunk_offset(chunk_index, x, y, spp);
-        let chunk = self.decoder.read_chunk(chunk_index).unwrap();
-        raster_value(&chunk, offset, spp)
+        let complex = self.image_info().complex_type();
+        let chunk = read_chunk(&mut self.decoder, chunk_index, complex, self.big_endian).unwrap();
+        let value = raster_value(&chunk, offset, spp);
+        match self.value_transform() {
+            Some(transform) => transform.apply(value),
+            None => value,
+        }
+    }
+
+    /// Raster values along a polyline
+    ///
+    /// Coordinates are in the raster CRS. Distances are in metres for
+    /// geographic rasters, in CRS units otherwise. Values are bilinear
+    /// interpolated, NoData and masked pixels are skipped.
+    pub fn profile(&mut self, line: &[Coordinate], step: ProfileStep) -> GeorasterResult<Profile> {
+        let origin = self.origin().ok_or(GeorasterError::NoGeoreference)?;
+        let pixel_size = self
+            .image_pixel_size()
+            .ok_or(GeorasterError::NoGeoreference)?;
+        let segments =
+            profile::sample_positions(line, step, self.is_geographic(), origin, pixel_size, Ok)?;
+        let margin = pixel_size[0].abs().max(pixel_size[1].abs());
+        let mut profile = Profile::default();
+        for samples in segments {
+            let Some(bbox) = BoundingBox::from_coords(samples.iter().map(|(_, coord)| *coord))
+            else {
+                continue;
+            };
+            let raster = GeoRaster::read_extent(self, &bbox.buffer(margin))?;
+            profile
+                .points
+                .extend(samples.into_iter().map(|(distance, coord)| ProfilePoint {
+                    distance,
+                    coord,
+                    value: raster.interpolate(coord),
+                }));
+        }
+        Ok(profile)
+    }
+
+    /// Line of sight between `from` and `to` in raster CRS
+    ///
+    /// See `viewshed::line_of_sight`, distances are in metres for
+    /// geographic rasters.
+    pub fn line_of_sight(
+        &mut self,
+        from: Coordinate,
+        to: Coordinate,
+        observer: &Observer,
+    ) -> GeorasterResult<Option<LineOfSight>> {
+        let resolution = GeoRaster::resolution(self).ok_or(GeorasterError::NoGeoreference)?;
+        let Some(bbox) = BoundingBox::from_coords([from, to]) else {
+            return Ok(None);
+        };
+        let dem = GeoRaster::read_extent(self, &bbox.buffer(resolution[0].max(resolution[1])))?;
+        Ok(viewshed::line_of_sight(
+            &dem,
+            self.ground_epsg(),
+            from,
+            to,
+            observer,
+        ))
+    }
+
+    /// Visible area within `radius` around `center` in raster CRS
+    ///
+    /// See `viewshed::viewshed`, `radius` is in metres for geographic rasters.
+    /// The returned raster covers the bounding box of the analysed area.
+    pub fn viewshed(
+        &mut self,
+        center: Coordinate,
+        radius: f64,
+        observer: &Observer,
+    ) -> GeorasterResult<Raster> {
+        let resolution = GeoRaster::resolution(self).ok_or(GeorasterError::NoGeoreference)?;
+        let epsg = self.ground_epsg();
+        let (scale_x, scale_y) = geo::ground_scale(epsg, center.y);
+        let bbox = BoundingBox::new(
+            center.x - radius / scale_x,
+            center.y - radius / scale_y,
+            center.x + radius / scale_x,
+            center.y + radius / scale_y,
+        );
+        let dem = GeoRaster::read_extent(self, &bbox.buffer(resolution[0].max(resolution[1])))?;
+        Ok(viewshed::viewshed(&dem, epsg, center, radius, observer))
+    }
+
+    /// CRS for conversion of distances into metres on the ground
+    fn ground_epsg(&self) -> Option<u32> {
+        geo::ground_epsg(self.epsg_code(), self.is_geographic())
     }
 
     /// Return raster value at geographical location
@@ -198,17 +476,40 @@ impl<R: Read + Seek + Send> GeoTiffReader<R> {
         }
     }
 
+    /// Reads the pixel at a location given in the CRS with EPSG code `epsg`
+    ///
+    /// The location is reprojected into the CRS of the raster from its GeoKeys,
+    /// e.g. `read_pixel_at_location_with_crs((lon, lat), 4326)` for WGS84 input.
+    /// Use `crs::Transformer` directly for querying many locations.
+    #[cfg(feature = "geodesy")]
+    pub fn read_pixel_at_location_with_crs(
+        &mut self,
+        coord: impl Into<Coordinate>,
+        epsg: u32,
+    ) -> GeorasterResult<RasterValue> {
+        let raster_epsg = self
+            .epsg_code()
+            .ok_or_else(|| GeorasterError::UnsupportedCrs("Unknown raster CRS".to_string()))?;
+        let transformer = Transformer::new(epsg, raster_epsg)?;
+        Ok(self.read_pixel_at_location(transformer.transform(coord)?))
+    }
+
     /// Returns an Iterator over the pixels of an image part.
     /// The iterator yields the coordinates of each pixel
     /// along with their value
-    pub fn pixels(&mut self, x: u32, y: u32, width: u32, height: u32) -> Pixels<R> {
+    pub fn pixels(&mut self, x: u32, y: u32, width: u32, height: u32) -> Pixels<'_, R> {
         let image_dims = self.dimensions_or_zero();
         let chunk_dims = self.decoder.chunk_dimensions();
         let dims =
             TileAttributes::from_dims(image_dims, chunk_dims, self.image_info().planar_config);
         let spp = self.spp();
+        let complex = self.image_info().complex_type();
+        let transform = self.value_transform();
         Pixels {
             decoder: &mut self.decoder,
+            complex,
+            transform,
+            big_endian: self.big_endian,
             chunk: Err(TiffError::LimitsExceeded),
             offset: 0,
             x,
@@ -225,30 +526,522 @@ impl<R: Read + Seek + Send> GeoTiffReader<R> {
         }
     }
 
-    /// Converts a `Coordinate` into pixel based on the geoinformation in the tiff
+    /// Read a window of the current image into a row-major vector
     ///
-    /// Returns the `None` variant when geoinformation is not available.
+    /// Pixels outside of the image are returned as `RasterValue::NoData`.
+    pub fn read_window(&mut self, x: u32, y: u32, width: u32, height: u32) -> Vec<RasterValue> {
+        let mut values = vec![RasterValue::NoData; width as usize * height as usize];
+        let (image_width, image_height) = self.dimensions_or_zero();
+        let w = (x + width).min(image_width).saturating_sub(x);
+        let h = (y + height).min(image_height).saturating_sub(y);
+        if w == 0 || h == 0 {
+            return values;
+        }
+        for (px, py, value) in self.pixels(x, y, w, h) {
+            values[(py - y) as usize * width as usize + (px - x) as usize] = value;
+        }
+        values
+    }
+
+    /// Converts a `Coordinate` into the pixel of the current image containing it
+    ///
+    /// Returns the `None` variant when geoinformation is not available or
+    /// the coordinate is outside of the image.
     pub fn coord_to_pixel(&self, coord: impl Into<Coordinate>) -> Option<(u32, u32)> {
         let (origin_x, origin_y) = self.origin()?.into();
-        let (pixel_size_x, pixel_size_y) = self.pixel_size()?.into();
+        let (pixel_size_x, pixel_size_y) = self.image_pixel_size()?.into();
+        let (width, height) = self.image_info().dimensions.unwrap_or((0, 0));
         let coord = coord.into();
-        Some((
-            ((coord.x - origin_x) / pixel_size_x).round() as u32,
-            ((coord.y - origin_y) / pixel_size_y).round() as u32,
-        ))
+        let x = ((coord.x - origin_x) / pixel_size_x).floor();
+        let y = ((coord.y - origin_y) / pixel_size_y).floor();
+        if !(x >= 0.0 && y >= 0.0 && x < width as f64 && y < height as f64) {
+            return None;
+        }
+        Some((x as u32, y as u32))
     }
 
-    /// Converts a pixel position into geocoordinates
+    /// Converts a pixel position of the current image into the geocoordinates
+    /// of its upper left corner
     ///
     /// Returns the `None` variant when geoinformation is not available.
     pub fn pixel_to_coord(&self, x: u32, y: u32) -> Option<Coordinate> {
         let (origin_x, origin_y) = self.origin()?.into();
-        let (pixel_size_x, pixel_size_y) = self.pixel_size()?.into();
+        let (pixel_size_x, pixel_size_y) = self.image_pixel_size()?.into();
         Some(Coordinate {
             x: x as f64 * pixel_size_x + origin_x,
             y: y as f64 * pixel_size_y + origin_y,
         })
     }
+
+    /// Compute statistics of a band (starting with 1)
+    ///
+    /// NoData, NaN and masked values are excluded, scale and offset are applied when
+    /// enabled with `apply_scale_offset`. Complex values are counted by their
+    /// amplitude. The full resolution image is used, unless `approx` is set,
+    /// which uses the smallest overview instead.
+    ///
+    /// ```rust
+    /// use std::{fs::File, io::BufReader};
+    /// use georaster::geotiff::GeoTiffReader;
+    ///
+    /// let img_file = BufReader::new(File::open("data/tiff/byte.tif").unwrap());
+    /// let mut tiff = GeoTiffReader::open(img_file).unwrap();
+    ///
+    /// let stats = tiff.statistics(1, false).unwrap();
+    /// assert_eq!((stats.min, stats.max), (74.0, 255.0));
+    /// ```
+    pub fn statistics(&mut self, band: u8, approx: bool) -> GeorasterResult<Statistics> {
+        let mut builder = StatisticsBuilder::default();
+        self.for_each_value(band, approx, |value| builder.add(value))?;
+        Ok(builder.build())
+    }
+
+    /// Compute histogram of a band (starting with 1) with `bins` equally sized bins
+    ///
+    /// Values outside of `range` are ignored. Without `range`, the histogram covers
+    /// the minimum and maximum value of the band. See `statistics` for `approx`.
+    pub fn histogram(
+        &mut self,
+        band: u8,
+        bins: usize,
+        range: Option<(f64, f64)>,
+        approx: bool,
+    ) -> GeorasterResult<Histogram> {
+        let (min, max) = match range {
+            Some(range) => range,
+            None => {
+                let stats = self.statistics(band, approx)?;
+                (stats.min, stats.max)
+            }
+        };
+        let mut histogram = Histogram::new(min, max, bins);
+        self.for_each_value(band, approx, |value| {
+            if let Some(v) = value {
+                histogram.add(v);
+            }
+        })?;
+        Ok(histogram)
+    }
+
+    /// Read window with NoData values and masked pixels as `RasterValue::NoData`
+    pub(crate) fn read_masked_window(
+        &mut self,
+        x: u32,
+        y: u32,
+        width: u32,
+        height: u32,
+    ) -> GeorasterResult<Vec<RasterValue>> {
+        let mut values = self.read_window(x, y, width, height);
+        // NoData values are already replaced when applying scale and offset
+        if let (Some(nodata), None) = (self.nodata, self.value_transform()) {
+            for value in values.iter_mut() {
+                match value.as_f64() {
+                    Some(v) if v == nodata || (v.is_nan() && nodata.is_nan()) => {
+                        *value = RasterValue::NoData
+                    }
+                    _ => {}
+                }
+            }
+        }
+        if let Some(valid) = self.read_mask_window(x, y, width, height)? {
+            for (value, valid) in values.iter_mut().zip(valid) {
+                if !valid {
+                    *value = RasterValue::NoData;
+                }
+            }
+        }
+        Ok(values)
+    }
+
+    /// Validity of pixels in a window of the current image from its internal mask
+    ///
+    /// Decoded mask chunks are cached, the mask image is only loaded for missing chunks.
+    fn read_mask_window(
+        &mut self,
+        x: u32,
+        y: u32,
+        width: u32,
+        height: u32,
+    ) -> GeorasterResult<Option<Vec<bool>>> {
+        let Some(mask) = &self.masks[self.cur_image_idx] else {
+            return Ok(None);
+        };
+        let dims = &mask.dims;
+        let x1 = (x + width).min(dims.image_width as u32);
+        let y1 = (y + height).min(dims.image_height as u32);
+        if x >= x1 || y >= y1 {
+            return Ok(Some(vec![true; width as usize * height as usize]));
+        }
+        let (tile_width, tile_length) = (dims.tile_width as u32, dims.tile_length as u32);
+        let mut chunks = Vec::new();
+        for row in y / tile_length..=(y1 - 1) / tile_length {
+            for col in x / tile_width..=(x1 - 1) / tile_width {
+                let chunk = dims.get_chunk_index(col * tile_width, row * tile_length, 0);
+                chunks.push(chunk);
+            }
+        }
+        if chunks
+            .iter()
+            .any(|chunk| !self.mask_chunks.contains_key(&(mask.index, *chunk)))
+        {
+            if self.mask_chunks.len() + chunks.len() > MASK_CACHE_SIZE {
+                self.mask_chunks.clear();
+            }
+            chunks.retain(|chunk| !self.mask_chunks.contains_key(&(mask.index, *chunk)));
+            self.decoder.seek_to_image(mask.index)?;
+            let decoded = chunks
+                .into_iter()
+                .map(|chunk| Ok((chunk, self.decoder.read_chunk(chunk)?)))
+                .collect::<TiffResult<Vec<_>>>();
+            self.decoder.seek_to_image(self.cur_image_idx)?;
+            for (chunk, result) in decoded? {
+                self.mask_chunks.insert((mask.index, chunk), result);
+            }
+        }
+        let mut valid = vec![true; width as usize * height as usize];
+        for py in y..y1 {
+            for px in x..x1 {
+                let chunk = dims.get_chunk_index(px, py, 0);
+                let Some(result) = self.mask_chunks.get(&(mask.index, chunk)) else {
+                    continue;
+                };
+                let offset = dims.get_chunk_offset(chunk, px, py, 1);
+                valid[(py - y) as usize * width as usize + (px - x) as usize] =
+                    raster_value(result, offset, 1).as_f64() != Some(0.0);
+            }
+        }
+        Ok(Some(valid))
+    }
+
+    /// Index of smallest image, which is not a mask
+    fn smallest_overview(&self) -> Option<usize> {
+        let samples = self.images.first()?.samples;
+        self.images
+            .iter()
+            .enumerate()
+            .filter(|(_, img)| !img.is_mask() && img.samples == samples)
+            .min_by_key(|(_, img)| {
+                img.dimensions
+                    .map(|(w, h)| w as u64 * h as u64)
+                    .unwrap_or(u64::MAX)
+            })
+            .map(|(idx, _)| idx)
+    }
+
+    /// Statistics of the current band over pixels with centers inside `polygon`
+    ///
+    /// Coordinates are in the raster CRS. NoData and masked pixels are counted
+    /// as NoData, scale and offset are applied when enabled with
+    /// `apply_scale_offset`. Only chunks intersecting the polygon are decoded.
+    #[cfg(feature = "geo")]
+    pub fn zonal_stats(&mut self, polygon: &Polygon) -> GeorasterResult<ZonalStatistics> {
+        self.zonal_values(std::slice::from_ref(polygon))
+            .map(ZonalStatistics::from_values)
+    }
+
+    /// Statistics of the current band over pixels with centers inside `polygons`
+    ///
+    /// See `zonal_stats`.
+    #[cfg(feature = "geo")]
+    pub fn zonal_stats_multi(
+        &mut self,
+        polygons: &MultiPolygon,
+    ) -> GeorasterResult<ZonalStatistics> {
+        self.zonal_values(&polygons.0)
+            .map(ZonalStatistics::from_values)
+    }
+
+    /// Contour lines of the current band in the window `x`, `y`, `width`, `height`
+    ///
+    /// The window is extended by one pixel to the right and bottom, so contours
+    /// of adjacent windows meet at identical coordinates. NoData and masked
+    /// pixels interrupt contour lines, scale and offset are applied when enabled
+    /// with `apply_scale_offset`. See `contour::contours`.
+    #[cfg(feature = "geo")]
+    pub fn contours(
+        &mut self,
+        x: u32,
+        y: u32,
+        width: u32,
+        height: u32,
+        levels: &ContourLevels,
+    ) -> GeorasterResult<Vec<Contour>> {
+        let origin = self.origin().ok_or(GeorasterError::NoGeoreference)?;
+        let pixel_size = self
+            .image_pixel_size()
+            .ok_or(GeorasterError::NoGeoreference)?;
+        let (image_width, image_height) = self.dimensions_or_zero();
+        let w = (x + width + 1).min(image_width).saturating_sub(x);
+        let h = (y + height + 1).min(image_height).saturating_sub(y);
+        let values = self
+            .read_masked_window(x, y, w, h)?
+            .iter()
+            .map(|v| v.as_f64().filter(|v| !v.is_nan()))
+            .collect::<Vec<_>>();
+        let grid = contour::Grid {
+            values: &values,
+            width: w as usize,
+            height: h as usize,
+            origin,
+            pixel_size,
+            offset: (x, y),
+        };
+        Ok(grid.contours(levels))
+    }
+
+    /// Regions of connected pixels with equal value of a band (starting with 1)
+    ///
+    /// NoData and masked pixels are excluded, scale and offset are applied when
+    /// enabled with `apply_scale_offset`. See `polygonize::polygonize`.
+    #[cfg(feature = "geo")]
+    pub fn polygonize(
+        &mut self,
+        band: u8,
+        connectivity: Connectivity,
+    ) -> GeorasterResult<Vec<Region>> {
+        let origin = self.origin().ok_or(GeorasterError::NoGeoreference)?;
+        let pixel_size = self
+            .image_pixel_size()
+            .ok_or(GeorasterError::NoGeoreference)?;
+        let (width, height) = self.dimensions_or_zero();
+        let band_idx = self.band_idx;
+        self.select_raster_band(band)?;
+        let values = self.read_masked_window(0, 0, width, height);
+        self.band_idx = band_idx;
+        let raster = Raster {
+            width,
+            height,
+            values: values?,
+            origin,
+            pixel_size,
+        };
+        Ok(polygonize::polygonize(&raster, connectivity))
+    }
+
+    /// Values of pixels with centers inside `polygons`, read chunk by chunk
+    #[cfg(feature = "geo")]
+    fn zonal_values(&mut self, polygons: &[Polygon]) -> GeorasterResult<Vec<Option<f64>>> {
+        let origin = self.origin().ok_or(GeorasterError::NoGeoreference)?;
+        let pixel_size = self
+            .image_pixel_size()
+            .ok_or(GeorasterError::NoGeoreference)?;
+        let (width, height) = self.dimensions_or_zero();
+        let coords = polygons
+            .iter()
+            .flat_map(|p| p.exterior().coords().map(|c| Coordinate::from(*c)));
+        let Some(bbox) = BoundingBox::from_coords(coords) else {
+            return Ok(Vec::new());
+        };
+        let (x0, y0, x1, y1) = pixel_window(&bbox, origin, pixel_size, width, height);
+        let spans = geo::polygon_spans(polygons, origin, pixel_size, (x0, y0, x1, y1));
+        let (chunk_width, chunk_height) = self.chunk_dimensions();
+        let mut values = Vec::new();
+        for chunk_y in y0 / chunk_height..y1.div_ceil(chunk_height) {
+            let (wy0, wy1) = (
+                (chunk_y * chunk_height).max(y0),
+                ((chunk_y + 1) * chunk_height).min(y1),
+            );
+            // Spans are ordered by row
+            let row_start = spans.partition_point(|(row, _, _)| *row < wy0);
+            let row_end = spans.partition_point(|(row, _, _)| *row < wy1);
+            let row_spans = &spans[row_start..row_end];
+            for chunk_x in x0 / chunk_width..x1.div_ceil(chunk_width) {
+                let (wx0, wx1) = (
+                    (chunk_x * chunk_width).max(x0),
+                    ((chunk_x + 1) * chunk_width).min(x1),
+                );
+                if !row_spans
+                    .iter()
+                    .any(|(_, start, end)| *start < wx1 && *end > wx0)
+                {
+                    continue;
+                }
+                let window = self.read_masked_window(wx0, wy0, wx1 - wx0, wy1 - wy0)?;
+                for (row, start, end) in row_spans {
+                    for col in (*start).max(wx0)..(*end).min(wx1) {
+                        let idx =
+                            (row - wy0) as usize * (wx1 - wx0) as usize + (col - wx0) as usize;
+                        values.push(window[idx].as_f64());
+                    }
+                }
+            }
+        }
+        Ok(values)
+    }
+
+    /// Call `f` for each value of a band. Invalid values are passed as `None`.
+    fn for_each_value(
+        &mut self,
+        band: u8,
+        approx: bool,
+        f: impl FnMut(Option<f64>),
+    ) -> GeorasterResult<()> {
+        let samples = self.images.first().map(|img| img.samples).unwrap_or(0);
+        if band < 1 || band > samples {
+            return Err(TiffError::LimitsExceeded.into());
+        }
+        let image_idx = if approx {
+            self.smallest_overview().unwrap_or(0)
+        } else {
+            0
+        };
+        let cur_image_idx = self.cur_image_idx;
+        if image_idx != cur_image_idx {
+            self.seek_to_image(image_idx)?;
+        }
+        let result = self.for_each_image_value(band - 1, f);
+        if image_idx != cur_image_idx {
+            self.seek_to_image(cur_image_idx)?;
+        }
+        result
+    }
+
+    /// Stream over all chunks of the current image
+    fn for_each_image_value(
+        &mut self,
+        band_idx: u8,
+        mut f: impl FnMut(Option<f64>),
+    ) -> GeorasterResult<()> {
+        let dims = TileAttributes::from_dims(
+            self.dimensions_or_zero(),
+            self.chunk_dimensions(),
+            self.image_info().planar_config,
+        );
+        if dims.image_width == 0 || dims.image_height == 0 {
+            return Ok(());
+        }
+        let complex = self.image_info().complex_type();
+        let chunks = dims.chunks_per_band();
+        // Chunk range, samples per pixel in chunk, sample offset of band
+        let (chunk_indices, stride, sample_offset) = match self.image_info().planar_config {
+            Some(PlanarConfiguration::Planar) => {
+                let first = band_idx as usize * chunks;
+                (first..first + chunks, 1, 0)
+            }
+            _ if complex.is_some() => (0..chunks, 2, 0),
+            _ => (
+                0..chunks,
+                self.image_info().samples as usize,
+                band_idx as usize,
+            ),
+        };
+        let scale_offset = if self.apply_scale_offset {
+            self.metadata()
+                .and_then(|md| md.band(band_idx + 1))
+                .and_then(|band| band.scale_offset())
+        } else {
+            None
+        };
+        let transform = ValueTransform {
+            scale_offset,
+            nodata: self.nodata,
+        };
+        let value_spp = if complex.is_some() { 2 } else { 1 };
+        for idx in chunk_indices {
+            let chunk = read_chunk(&mut self.decoder, idx as u32, complex, self.big_endian)?;
+            let (padding_right, padding_down) = dims.get_padding(idx);
+            let width = dims.tile_width - padding_right;
+            let height = dims.tile_length - padding_down;
+            // Mask of the chunk area
+            let band_chunk = idx % chunks;
+            let x = band_chunk % dims.tiles_across() * dims.tile_width;
+            let y = band_chunk / dims.tiles_across() * dims.tile_length;
+            let valid = self.read_mask_window(x as u32, y as u32, width as u32, height as u32)?;
+            for i in 0..width * height {
+                if valid.as_ref().is_some_and(|valid| !valid[i]) {
+                    f(None);
+                    continue;
+                }
+                let value = raster_value(&chunk, i * stride + sample_offset, value_spp);
+                let value = transform.apply(value);
+                if complex.is_some() && value != RasterValue::NoData {
+                    f(Some(value.amplitude()))
+                } else {
+                    f(value.as_f64())
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+impl<R: Read + Seek + Send> GeoRaster for GeoTiffReader<R> {
+    fn extent(&self) -> Option<BoundingBox> {
+        let [origin_x, origin_y] = self.origin()?;
+        let [pixel_size_x, pixel_size_y] = self.image_pixel_size()?;
+        let (width, height) = self.image_info().dimensions?;
+        let (x, y) = (
+            origin_x + width as f64 * pixel_size_x,
+            origin_y + height as f64 * pixel_size_y,
+        );
+        Some(BoundingBox {
+            epsg: GeoTiffReader::epsg_code(self),
+            ..BoundingBox::new(
+                origin_x.min(x),
+                origin_y.min(y),
+                origin_x.max(x),
+                origin_y.max(y),
+            )
+        })
+    }
+
+    fn epsg_code(&self) -> Option<u32> {
+        GeoTiffReader::epsg_code(self)
+    }
+
+    fn resolution(&self) -> Option<[f64; 2]> {
+        let [pixel_size_x, pixel_size_y] = self.image_pixel_size()?;
+        Some([pixel_size_x.abs(), pixel_size_y.abs()])
+    }
+
+    fn band_count(&self) -> usize {
+        self.image_info().samples as usize
+    }
+
+    /// NoData values and pixels masked by an internal mask are returned as `RasterValue::NoData`
+    fn sample(&mut self, coord: Coordinate) -> GeorasterResult<RasterValue> {
+        if self.origin().is_none() || self.image_pixel_size().is_none() {
+            return Err(GeorasterError::NoGeoreference);
+        }
+        let Some((x, y)) = self.coord_to_pixel(coord) else {
+            return Ok(RasterValue::NoData);
+        };
+        let values = self.read_masked_window(x, y, 1, 1)?;
+        Ok(values[0])
+    }
+
+    /// NoData values and pixels masked by an internal mask are returned as `RasterValue::NoData`
+    fn read_extent(&mut self, bbox: &BoundingBox) -> GeorasterResult<Raster> {
+        let origin = self.origin().ok_or(GeorasterError::NoGeoreference)?;
+        let pixel_size = self
+            .image_pixel_size()
+            .ok_or(GeorasterError::NoGeoreference)?;
+        let (width, height) = self.dimensions_or_zero();
+        let (x0, y0, x1, y1) = pixel_window(bbox, origin, pixel_size, width, height);
+        let ul = self.pixel_to_coord(x0, y0).unwrap_or_default();
+        let mut raster = Raster::new(x1 - x0, y1 - y0, [ul.x, ul.y], pixel_size);
+        raster.values = self.read_masked_window(x0, y0, x1 - x0, y1 - y0)?;
+        Ok(raster)
+    }
+
+    /// Reads from the overview selected by `best_image` and restores the current image
+    fn read_extent_at(
+        &mut self,
+        bbox: &BoundingBox,
+        resolution: f64,
+        max_pixels: u64,
+    ) -> GeorasterResult<Raster> {
+        let [pixel_size_x, pixel_size_y] =
+            self.pixel_size().ok_or(GeorasterError::NoGeoreference)?;
+        // Tolerate rounding errors of reprojected resolutions
+        let factor = resolution / pixel_size_x.abs().min(pixel_size_y.abs()) * (1.0 + 1e-9);
+        let (image_idx, _) = self.best_image(factor);
+        let cur_image_idx = self.cur_image_idx;
+        self.seek_to_image(image_idx)?;
+        let raster = check_window_size(self, bbox, max_pixels).and_then(|_| self.read_extent(bbox));
+        self.seek_to_image(cur_image_idx)?;
+        raster
+    }
 }
 
 impl ImageInfo {
@@ -275,9 +1068,20 @@ impl ImageInfo {
             .try_into()
             .unwrap();
 
-        // https://awaresystems.be/imaging/tiff/tifftags/newsubfiletype.html
+        let sample_format = decoder
+            .find_tag_unsigned_vec::<u16>(Tag::SampleFormat)
+            .ok()
+            .flatten()
+            .and_then(|formats| formats.first().copied())
+            .map(SampleFormat::from_u16_exhaustive);
+
         // https://gdal.org/drivers/raster/gtiff.html#internal-nodata-masks
-        let _subfile_type = decoder.get_tag_u64(Tag::NewSubfileType);
+        let subfile_type = decoder.get_tag_u32(Tag::NewSubfileType).ok();
+
+        let gdal_metadata = decoder
+            .get_tag_ascii_string(Tag::Unknown(42112))
+            .ok()
+            .map(|xml| GdalMetadata::parse(&xml));
 
         ImageInfo {
             dimensions,
@@ -285,6 +1089,29 @@ impl ImageInfo {
             photometric_interpretation,
             planar_config,
             samples,
+            sample_format,
+            subfile_type,
+            gdal_metadata,
+        }
+    }
+
+    /// Image is a transparency mask
+    pub fn is_mask(&self) -> bool {
+        self.subfile_type.is_some_and(|t| t & 4 != 0)
+    }
+
+    fn complex_type(&self) -> Option<ComplexType> {
+        match (self.sample_format, self.colortype) {
+            (Some(SampleFormat::Unknown(5)), Some(tiff::ColorType::Gray(32))) => {
+                Some(ComplexType::CI16)
+            }
+            (Some(SampleFormat::Unknown(5)), Some(tiff::ColorType::Gray(64))) => {
+                Some(ComplexType::CI32)
+            }
+            (Some(SampleFormat::Unknown(6)), Some(tiff::ColorType::Gray(64))) => {
+                Some(ComplexType::CF32)
+            }
+            _ => None,
         }
     }
 }
@@ -292,6 +1119,9 @@ impl ImageInfo {
 /// Raster iterator
 pub struct Pixels<'a, R: Read + Seek> {
     decoder: &'a mut Decoder<R>,
+    complex: Option<ComplexType>,
+    transform: Option<ValueTransform>,
+    big_endian: bool,
     chunk: TiffResult<DecodingResult>,
     offset: usize,
     x: u32,
@@ -299,7 +1129,7 @@ pub struct Pixels<'a, R: Read + Seek> {
     col: u32,
     row: u32,
     dims: TileAttributes,
-    // Samples per pixel (Gray=1, RGB (single band) = 3, etc.)
+    // Samples per pixel (Gray=1, Complex=2, RGB (single band) = 3, etc.)
     spp: u8,
     band_idx: u8,
     min_x: u32,
@@ -346,6 +1176,10 @@ impl<R: Read + Seek> Iterator for Pixels<'_, R> {
             }
         }
         let val = raster_value(self.chunk.as_ref().unwrap(), self.offset, self.spp);
+        let val = match &self.transform {
+            Some(transform) => transform.apply(val),
+            None => val,
+        };
         Some((self.x, self.y, val))
     }
 }
@@ -355,13 +1189,173 @@ impl<R: Read + Seek> Pixels<'_, R> {
         let chunk_index = self.dims.get_chunk_index(self.x, self.y, self.band_idx);
         self.row = chunk_index / self.dims.tiles_across() as u32;
         self.col = chunk_index % self.dims.tiles_across() as u32;
-        self.chunk = self.decoder.read_chunk(chunk_index);
+        self.chunk = read_chunk(self.decoder, chunk_index, self.complex, self.big_endian);
         self.offset = self
             .dims
             .get_chunk_offset(chunk_index, self.x, self.y, self.spp);
     }
 }
 
+/// Conversion of raw values into physical values
+#[derive(Clone, Copy, Debug)]
+struct ValueTransform {
+    scale_offset: Option<(f64, f64)>,
+    nodata: Option<f64>,
+}
+
+impl ValueTransform {
+    fn apply(&self, value: RasterValue) -> RasterValue {
+        match (value.as_f64(), self.nodata) {
+            (Some(v), Some(nodata)) if v == nodata || (v.is_nan() && nodata.is_nan()) => {
+                RasterValue::NoData
+            }
+            _ => match self.scale_offset {
+                Some((scale, offset)) if value.as_f64().is_some() => value.scaled(scale, offset),
+                _ => value,
+            },
+        }
+    }
+}
+
+fn read_chunk<R: Read + Seek>(
+    decoder: &mut Decoder<R>,
+    chunk_index: u32,
+    complex: Option<ComplexType>,
+    big_endian: bool,
+) -> TiffResult<DecodingResult> {
+    if let Err(TiffError::UnsupportedError(TiffUnsupportedError::InterpretationWithBits(
+        PhotometricInterpretation::RGBPalette,
+        bits,
+    ))) = decoder.colortype()
+    {
+        if bits == [8] {
+            return read_palette_chunk(decoder, chunk_index);
+        }
+    }
+    let Some(complex) = complex else {
+        return decoder.read_chunk(chunk_index);
+    };
+    // Decode both parts of a complex sample as one unsigned integer
+    // and split it according to the byte order of the file.
+    let (width, height) = decoder.chunk_data_dimensions(chunk_index);
+    let len = width as usize * height as usize;
+    match complex {
+        ComplexType::CI16 => {
+            let mut buffer = vec![0u32; len];
+            decoder.read_chunk_to_buffer(
+                DecodingBuffer::U32(&mut buffer),
+                chunk_index,
+                width as usize,
+            )?;
+            let parts = buffer.iter().flat_map(|v| {
+                let (hi, lo) = ((v >> 16) as i16, *v as i16);
+                if big_endian {
+                    [hi, lo]
+                } else {
+                    [lo, hi]
+                }
+            });
+            Ok(DecodingResult::I16(parts.collect()))
+        }
+        ComplexType::CI32 | ComplexType::CF32 => {
+            let mut buffer = vec![0u64; len];
+            decoder.read_chunk_to_buffer(
+                DecodingBuffer::U64(&mut buffer),
+                chunk_index,
+                width as usize,
+            )?;
+            let parts = buffer.iter().flat_map(|v| {
+                let (hi, lo) = ((v >> 32) as u32, *v as u32);
+                if big_endian {
+                    [hi, lo]
+                } else {
+                    [lo, hi]
+                }
+            });
+            if let ComplexType::CF32 = complex {
+                Ok(DecodingResult::F32(parts.map(f32::from_bits).collect()))
+            } else {
+                Ok(DecodingResult::I32(parts.map(|v| v as i32).collect()))
+            }
+        }
+    }
+}
+
+/// Read color indices of an 8 bit paletted image, which are not decoded by `tiff`
+///
+/// Only chunks without compression or with LZW or Deflate compression are supported.
+/// Paletted images with other bit depths are rejected by `tiff`.
+fn read_palette_chunk<R: Read + Seek>(
+    decoder: &mut Decoder<R>,
+    chunk_index: u32,
+) -> TiffResult<DecodingResult> {
+    let (offsets, byte_counts) = match decoder.get_chunk_type() {
+        ChunkType::Strip => (Tag::StripOffsets, Tag::StripByteCounts),
+        ChunkType::Tile => (Tag::TileOffsets, Tag::TileByteCounts),
+    };
+    let idx = chunk_index as usize;
+    let missing = || TiffError::FormatError(TiffFormatError::InconsistentSizesEncountered);
+    let offset = *decoder
+        .get_tag_u64_vec(offsets)?
+        .get(idx)
+        .ok_or_else(missing)?;
+    let byte_count = *decoder
+        .get_tag_u64_vec(byte_counts)?
+        .get(idx)
+        .ok_or_else(missing)?;
+    let compression = decoder
+        .find_tag_unsigned(Tag::Compression)?
+        .unwrap_or(CompressionMethod::None.to_u16());
+    let predictor = decoder.find_tag_unsigned(Tag::Predictor)?.unwrap_or(1u16);
+
+    decoder.goto_offset_u64(offset)?;
+    let data = (0..byte_count)
+        .map(|_| decoder.read_byte())
+        .collect::<Result<Vec<_>, _>>()?;
+    let mut data = match CompressionMethod::from_u16_exhaustive(compression) {
+        CompressionMethod::None => data,
+        CompressionMethod::LZW => {
+            weezl::decode::Decoder::with_tiff_size_switch(weezl::BitOrder::Msb, 8)
+                .decode(&data)
+                .map_err(|e| TiffError::FormatError(TiffFormatError::Format(e.to_string())))?
+        }
+        CompressionMethod::Deflate | CompressionMethod::OldDeflate => {
+            let mut decoded = Vec::new();
+            flate2::read::ZlibDecoder::new(&data[..]).read_to_end(&mut decoded)?;
+            decoded
+        }
+        method => {
+            return Err(TiffError::UnsupportedError(
+                TiffUnsupportedError::UnsupportedCompressionMethod(method),
+            ))
+        }
+    };
+
+    // Tiles are padded to the full tile width
+    let (width, height) = decoder.chunk_data_dimensions(chunk_index);
+    let stride = match decoder.get_chunk_type() {
+        ChunkType::Strip => width as usize,
+        ChunkType::Tile => decoder.chunk_dimensions().0 as usize,
+    };
+    if data.len() < stride * height as usize {
+        return Err(TiffError::FormatError(
+            TiffFormatError::InconsistentSizesEncountered,
+        ));
+    }
+    let rows = data.chunks_exact_mut(stride).take(height as usize);
+    let mut values = Vec::with_capacity(width as usize * height as usize);
+    for row in rows {
+        // Horizontal differencing
+        if predictor == 2 {
+            for i in 1..row.len() {
+                row[i] = row[i].wrapping_add(row[i - 1]);
+            }
+        }
+        values.extend_from_slice(&row[..width as usize]);
+    }
+    Ok(DecodingResult::U8(values))
+}
+
 fn raster_value(chunk: &DecodingResult, offset: usize, spp: u8) -> RasterValue {
     match chunk {
         DecodingResult::U8(chunk) => match spp {
@@ -418,11 +1412,38 @@ fn raster_value(chunk: &DecodingResult, offset: usize, spp: u8) -> RasterValue {
         },
         DecodingResult::U32(chunk) => chunk.get(offset).map(|v| RasterValue::U32(*v)),
         DecodingResult::U64(chunk) => chunk.get(offset).map(|v| RasterValue::U64(*v)),
-        DecodingResult::F32(chunk) => chunk.get(offset).map(|v| RasterValue::F32(*v)),
+        DecodingResult::F32(chunk) => match spp {
+            2 => {
+                if let (Some(re), Some(im)) = (chunk.get(offset), chunk.get(offset + 1)) {
+                    Some(RasterValue::CF32(*re, *im))
+                } else {
+                    None
+                }
+            }
+            _ => chunk.get(offset).map(|v| RasterValue::F32(*v)),
+        },
         DecodingResult::F64(chunk) => chunk.get(offset).map(|v| RasterValue::F64(*v)),
         DecodingResult::I8(chunk) => chunk.get(offset).map(|v| RasterValue::I8(*v)),
-        DecodingResult::I16(chunk) => chunk.get(offset).map(|v| RasterValue::I16(*v)),
-        DecodingResult::I32(chunk) => chunk.get(offset).map(|v| RasterValue::I32(*v)),
+        DecodingResult::I16(chunk) => match spp {
+            2 => {
+                if let (Some(re), Some(im)) = (chunk.get(offset), chunk.get(offset + 1)) {
+                    Some(RasterValue::CI16(*re, *im))
+                } else {
+                    None
+                }
+            }
+            _ => chunk.get(offset).map(|v| RasterValue::I16(*v)),
+        },
+        DecodingResult::I32(chunk) => match spp {
+            2 => {
+                if let (Some(re), Some(im)) = (chunk.get(offset), chunk.get(offset + 1)) {
+                    Some(RasterValue::CI32(*re, *im))
+                } else {
+                    None
+                }
+            }
+            _ => chunk.get(offset).map(|v| RasterValue::I32(*v)),
+        },
         DecodingResult::I64(chunk) => chunk.get(offset).map(|v| RasterValue::I64(*v)),
     }
     .unwrap_or(RasterValue::NoData)
@@ -467,6 +1488,8 @@ impl TileAttributes {
         (self.tile_length - self.image_height % self.tile_length) % self.tile_length
     }
     pub fn get_padding(&self, tile: usize) -> (usize, usize) {
+        // Index within band for planar images
+        let tile = tile % self.chunks_per_band();
         let row = tile / self.tiles_across();
         let column = tile % self.tiles_across();
 
@@ -485,6 +1508,11 @@ impl TileAttributes {
         (padding_right, padding_down)
     }
 
+    /// Number of tiles or stripes of one band
+    fn chunks_per_band(&self) -> usize {
+        self.tiles_across() * self.tiles_down()
+    }
+
     /// Return tile or stripe index of a pixel
     fn get_chunk_index(&self, x: u32, y: u32, band: u8) -> u32 {
         let x = x as usize;
@@ -493,7 +1521,7 @@ impl TileAttributes {
         assert!(x < self.image_width);
         assert!(y < self.image_height);
         let band_offset = match self.planar_config {
-            PlanarConfiguration::Planar => (self.image_height / self.tile_length) * band,
+            PlanarConfiguration::Planar => self.chunks_per_band() * band,
             _ => 0,
         };
         let x_chunks = x / self.tile_width;
@@ -0,0 +1,170 @@
+//! GeoTIFF writer for in-memory rasters.
+
+use crate::{GeorasterError, GeorasterResult, Raster, RasterValue, SampleType};
+use std::io::{Seek, Write};
+use tiff::encoder::{colortype, TiffEncoder, TiffValue};
+use tiff::tags::Tag;
+
+/// GeoTIFF writer
+///
+/// Writes single band rasters with a numeric sample type as uncompressed
+/// GeoTIFF with the grid of the raster.
+///
+/// ```rust
+/// use georaster::{geotiff_writer::GeoTiffWriter, Raster, RasterValue};
+/// use std::io::Cursor;
+///
+/// let mut raster = Raster::new(2, 2, [7.0, 47.0], [0.5, -0.5]);
+/// raster.set(0, 0, RasterValue::U8(1));
+/// let mut tiff = Cursor::new(Vec::new());
+/// GeoTiffWriter::new()
+///     .epsg(4326)
+///     .nodata(0.0)
+///     .write(&raster, &mut tiff)
+///     .unwrap();
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct GeoTiffWriter {
+    epsg: Option<u32>,
+    geographic: bool,
+    nodata: Option<f64>,
+}
+
+impl GeoTiffWriter {
+    pub fn new() -> Self {
+        GeoTiffWriter::default()
+    }
+
+    /// Set projected CRS or geographic WGS84 (4326)
+    ///
+    /// Use `crs` for other geographic CRS.
+    pub fn epsg(self, epsg: u32) -> Self {
+        self.crs(epsg, epsg == 4326)
+    }
+
+    /// Set CRS, written as geographic or projected CRS
+    pub fn crs(mut self, epsg: u32, geographic: bool) -> Self {
+        self.epsg = Some(epsg);
+        self.geographic = geographic;
+        self
+    }
+
+    /// Set NoData value, which replaces `RasterValue::NoData` pixels
+    pub fn nodata(mut self, nodata: f64) -> Self {
+        self.nodata = Some(nodata);
+        self
+    }
+
+    /// Write `raster` into `writer`
+    ///
+    /// The sample type is taken from the raster values. Rasters without values
+    /// are written as `F64`. Returns `GeorasterError::ValueRange` for mixed or
+    /// non-numeric values, for a NoData value which can't be represented in
+    /// the sample type and for NoData pixels without NoData value of integer
+    /// rasters.
+    pub fn write<W: Write + Seek>(&self, raster: &Raster, writer: W) -> GeorasterResult<()> {
+        let sample_type = raster
+            .values
+            .iter()
+            .find(|v| **v != RasterValue::NoData)
+            .map(|v| v.sample_type().ok_or(GeorasterError::ValueRange))
+            .transpose()?
+            .unwrap_or(SampleType::F64);
+        let nodata = match self.nodata {
+            Some(nodata) => RasterValue::F64(nodata).try_cast_to(sample_type)?,
+            None => RasterValue::F64(f64::NAN).cast_to(sample_type),
+        };
+        let mut encoder = TiffEncoder::new(writer)?;
+        macro_rules! write_samples {
+            ($variant:ident, $colortype:ty) => {
+                self.write_image::<$colortype, _>(
+                    &mut encoder,
+                    raster,
+                    &samples(raster, nodata, |v| match v {
+                        RasterValue::$variant(v) => Some(v),
+                        _ => None,
+                    })?,
+                )
+            };
+        }
+        match sample_type {
+            SampleType::U8 => write_samples!(U8, colortype::Gray8),
+            SampleType::U16 => write_samples!(U16, colortype::Gray16),
+            SampleType::U32 => write_samples!(U32, colortype::Gray32),
+            SampleType::U64 => write_samples!(U64, colortype::Gray64),
+            SampleType::F32 => write_samples!(F32, colortype::Gray32Float),
+            SampleType::F64 => write_samples!(F64, colortype::Gray64Float),
+            SampleType::I8 => write_samples!(I8, colortype::GrayI8),
+            SampleType::I16 => write_samples!(I16, colortype::GrayI16),
+            SampleType::I32 => write_samples!(I32, colortype::GrayI32),
+            SampleType::I64 => write_samples!(I64, colortype::GrayI64),
+        }
+    }
+
+    fn write_image<C: colortype::ColorType, W: Write + Seek>(
+        &self,
+        encoder: &mut TiffEncoder<W>,
+        raster: &Raster,
+        data: &[C::Inner],
+    ) -> GeorasterResult<()>
+    where
+        [C::Inner]: TiffValue,
+    {
+        let mut image = encoder.new_image::<C>(raster.width, raster.height)?;
+        let directory = image.encoder();
+        directory.write_tag(
+            Tag::ModelPixelScaleTag,
+            &[raster.pixel_size[0], -raster.pixel_size[1], 0.0][..],
+        )?;
+        directory.write_tag(
+            Tag::ModelTiepointTag,
+            &[0.0, 0.0, 0.0, raster.origin[0], raster.origin[1], 0.0][..],
+        )?;
+        directory.write_tag(Tag::GeoKeyDirectoryTag, &self.geo_keys()?[..])?;
+        if let Some(nodata) = self.nodata {
+            directory.write_tag(Tag::GdalNodata, &*nodata.to_string())?;
+        }
+        image.write_data(data)?;
+        Ok(())
+    }
+
+    /// GeoKeyDirectoryTag with header and key entries
+    fn geo_keys(&self) -> GeorasterResult<Vec<u16>> {
+        // GTRasterTypeGeoKey: RasterPixelIsArea
+        let mut keys = vec![[1025, 0, 1, 1]];
+        if let Some(epsg) = self.epsg {
+            let code = u16::try_from(epsg)
+                .map_err(|_| GeorasterError::UnsupportedCrs(format!("EPSG:{epsg}")))?;
+            if self.geographic {
+                // GTModelTypeGeoKey and GeographicTypeGeoKey
+                keys.extend([[1024, 0, 1, 2], [2048, 0, 1, code]]);
+            } else {
+                // GTModelTypeGeoKey and ProjectedCSTypeGeoKey
+                keys.extend([[1024, 0, 1, 1], [3072, 0, 1, code]]);
+            }
+        }
+        keys.sort();
+        let header = [1, 1, 0, keys.len() as u16];
+        Ok(header
+            .into_iter()
+            .chain(keys.into_iter().flatten())
+            .collect())
+    }
+}
+
+/// Samples of `raster` with `nodata` for NoData pixels
+fn samples<T>(
+    raster: &Raster,
+    nodata: RasterValue,
+    sample: impl Fn(RasterValue) -> Option<T>,
+) -> GeorasterResult<Vec<T>> {
+    raster
+        .values
+        .iter()
+        .map(|value| match value {
+            RasterValue::NoData => sample(nodata),
+            value => sample(*value),
+        })
+        .map(|sample| sample.ok_or(GeorasterError::ValueRange))
+        .collect()
+}
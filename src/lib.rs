@@ -1,12 +1,34 @@
 //! Library for accessing geospatial raster images.
 
+#[cfg(feature = "geo")]
+pub mod contour;
+#[cfg(feature = "geodesy")]
+pub mod crs;
 pub mod geo;
 pub mod geotiff;
+pub mod geotiff_writer;
+pub mod metadata;
 #[cfg(feature = "pmtiles")]
 pub mod pmtiles;
+#[cfg(feature = "pmtiles")]
+pub mod pmtiles_writer;
+#[cfg(feature = "geo")]
+pub mod polygonize;
+pub mod profile;
+pub mod raster;
+#[cfg(feature = "geo")]
+pub mod rasterize;
+pub mod stats;
+pub mod terrain;
+pub mod viewshed;
+#[cfg(feature = "geodesy")]
+pub mod warp;
 
-pub use geo::Coordinate;
+pub use geo::{BoundingBox, Coordinate};
+pub use raster::{AsyncGeoRaster, GeoRaster, Raster};
+use std::cmp::Ordering;
 use std::fmt;
+use std::str::FromStr;
 use thiserror::Error;
 
 #[derive(Error, Debug)]
@@ -17,6 +39,10 @@ pub enum GeorasterError {
     Io(#[from] std::io::Error),
     #[error("Tiff error - {0}")]
     Tiff(#[from] tiff::TiffError),
+    #[error("Missing georeference")]
+    NoGeoreference,
+    #[error("Unsupported CRS - {0}")]
+    UnsupportedCrs(String),
     #[cfg(feature = "pmtiles")]
     #[error("Image error - {0}")]
     Image(#[from] image::ImageError),
@@ -26,11 +52,21 @@ pub enum GeorasterError {
     #[cfg(feature = "pmtiles")]
     #[error("TMS error - {0}")]
     Tms(#[from] tile_grid::TmsError),
+    #[cfg(feature = "pmtiles")]
+    #[error("TMS registry error - {0}")]
+    TmsRegistry(#[from] tile_grid::Error),
+    #[cfg(feature = "geodesy")]
+    #[error("Geodesy error - {0}")]
+    Geodesy(#[from] geodesy::Error),
+    #[error("Invalid metadata - {0}")]
+    InvalidMetadata(String),
+    #[error("Raster window too large - {0} pixels exceed limit of {1} pixels")]
+    WindowTooLarge(u64, u64),
 }
 
 pub type GeorasterResult<T> = Result<T, GeorasterError>;
 
-#[derive(PartialEq, Debug)]
+#[derive(Clone, Copy, PartialEq, Debug)]
 #[non_exhaustive]
 pub enum RasterValue {
     NoData,
@@ -48,6 +84,150 @@ pub enum RasterValue {
     Rgba8(u8, u8, u8, u8),
     Rgb16(u16, u16, u16),
     Rgba16(u16, u16, u16, u16),
+    Rgb32F(f32, f32, f32),
+    Rgba32F(f32, f32, f32, f32),
+    /// Complex int16 (real, imaginary)
+    CI16(i16, i16),
+    /// Complex int32 (real, imaginary)
+    CI32(i32, i32),
+    /// Complex float32 (real, imaginary)
+    CF32(f32, f32),
+}
+
+/// Sample type of single band raster values
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum SampleType {
+    U8,
+    U16,
+    U32,
+    U64,
+    F32,
+    F64,
+    I8,
+    I16,
+    I32,
+    I64,
+}
+
+impl SampleType {
+    /// Smallest value of sample type
+    pub fn min_value(&self) -> f64 {
+        match self {
+            SampleType::U8 | SampleType::U16 | SampleType::U32 | SampleType::U64 => 0.0,
+            SampleType::F32 => f32::MIN as f64,
+            SampleType::F64 => f64::MIN,
+            SampleType::I8 => i8::MIN as f64,
+            SampleType::I16 => i16::MIN as f64,
+            SampleType::I32 => i32::MIN as f64,
+            SampleType::I64 => i64::MIN as f64,
+        }
+    }
+
+    /// Largest value of sample type
+    pub fn max_value(&self) -> f64 {
+        match self {
+            SampleType::U8 => u8::MAX as f64,
+            SampleType::U16 => u16::MAX as f64,
+            SampleType::U32 => u32::MAX as f64,
+            SampleType::U64 => u64::MAX as f64,
+            SampleType::F32 => f32::MAX as f64,
+            SampleType::F64 => f64::MAX,
+            SampleType::I8 => i8::MAX as f64,
+            SampleType::I16 => i16::MAX as f64,
+            SampleType::I32 => i32::MAX as f64,
+            SampleType::I64 => i64::MAX as f64,
+        }
+    }
+}
+
+/// Encoding of elevation values in raster tiles
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum ElevationEncoding {
+    /// Terrarium RGB: `(R * 256 + G + B / 256) - 32768`
+    Terrarium,
+    /// Mapbox Terrain-RGB: `-10000 + (R * 256 * 256 + G * 256 + B) * 0.1`
+    Mapbox,
+    /// Unencoded values
+    Raw,
+}
+
+impl ElevationEncoding {
+    /// Encode height in meters as RGB. Returns `None` for `Raw`.
+    ///
+    /// ```rust
+    /// use georaster::ElevationEncoding;
+    ///
+    /// assert_eq!(ElevationEncoding::Terrarium.encode_rgb(1607.5), Some([134, 71, 128]));
+    /// assert_eq!(ElevationEncoding::Mapbox.encode_rgb(1607.5), Some([1, 197, 107]));
+    /// ```
+    pub fn encode_rgb(&self, height: f64) -> Option<[u8; 3]> {
+        match self {
+            ElevationEncoding::Terrarium => {
+                let v = (height + 32768.0).clamp(0.0, 65535.0 + 255.0 / 256.0);
+                let int = v.floor();
+                let b = ((v - int) * 256.0).floor() as u8;
+                Some([(int as u32 / 256) as u8, (int as u32 % 256) as u8, b])
+            }
+            ElevationEncoding::Mapbox => {
+                let v = ((height + 10000.0) * 10.0).round().clamp(0.0, 16777215.0) as u32;
+                Some([(v >> 16) as u8, (v >> 8) as u8, v as u8])
+            }
+            ElevationEncoding::Raw => None,
+        }
+    }
+
+    /// Decode raster value to height in meters
+    ///
+    /// Returns `None` for NoData, transparent pixels (alpha 0) and
+    /// RGB values with all channels zero.
+    ///
+    /// ```rust
+    /// use georaster::{ElevationEncoding, RasterValue};
+    ///
+    /// let value = RasterValue::Rgb8(1, 197, 107);
+    /// assert_eq!(ElevationEncoding::Mapbox.decode(value), Some(1607.5));
+    /// assert_eq!(ElevationEncoding::Mapbox.decode(RasterValue::Rgba8(1, 197, 107, 0)), None);
+    /// ```
+    pub fn decode(&self, value: RasterValue) -> Option<f64> {
+        let rgb = match value {
+            RasterValue::Rgba8(_, _, _, 0) | RasterValue::Rgba16(_, _, _, 0) => return None,
+            RasterValue::Rgb8(r, g, b) | RasterValue::Rgba8(r, g, b, _) => {
+                Some((r as u16, g as u16, b as u16))
+            }
+            RasterValue::Rgb16(r, g, b) | RasterValue::Rgba16(r, g, b, _) => Some((r, g, b)),
+            _ => None,
+        };
+        let height = match (self, rgb) {
+            (_, Some((0, 0, 0))) => return None,
+            (ElevationEncoding::Terrarium, Some((r, g, b))) => decode_terrarium_rgb(r, g, b),
+            (ElevationEncoding::Mapbox, Some((r, g, b))) => decode_mapbox_rgb(r, g, b),
+            _ => value.as_f64()?,
+        };
+        (!height.is_nan()).then_some(height)
+    }
+}
+
+impl fmt::Display for ElevationEncoding {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ElevationEncoding::Terrarium => write!(f, "terrarium"),
+            ElevationEncoding::Mapbox => write!(f, "mapbox"),
+            ElevationEncoding::Raw => write!(f, "raw"),
+        }
+    }
+}
+
+impl FromStr for ElevationEncoding {
+    type Err = GeorasterError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_ascii_lowercase().as_str() {
+            "terrarium" => Ok(ElevationEncoding::Terrarium),
+            "mapbox" | "terrain-rgb" => Ok(ElevationEncoding::Mapbox),
+            "raw" => Ok(ElevationEncoding::Raw),
+            _ => Err(GeorasterError::ValueRange),
+        }
+    }
 }
 
 impl fmt::Display for RasterValue {
@@ -67,6 +247,11 @@ impl fmt::Display for RasterValue {
             RasterValue::Rgb16(r, g, b) => write!(f, "({r},{g},{b})"),
             RasterValue::Rgba8(r, g, b, a) => write!(f, "({r},{g},{b},{a})"),
             RasterValue::Rgba16(r, g, b, a) => write!(f, "({r},{g},{b},{a})"),
+            RasterValue::Rgb32F(r, g, b) => write!(f, "({r},{g},{b})"),
+            RasterValue::Rgba32F(r, g, b, a) => write!(f, "({r},{g},{b},{a})"),
+            RasterValue::CI16(re, im) => write!(f, "{re}{im:+}i"),
+            RasterValue::CI32(re, im) => write!(f, "{re}{im:+}i"),
+            RasterValue::CF32(re, im) => write!(f, "{re}{im:+}i"),
             _ => write!(f, "<NoData>"),
         }
     }
@@ -124,7 +309,7 @@ impl TryFrom<RasterValue> for f64 {
 }
 
 fn decode_terrarium_rgb(r: u16, g: u16, b: u16) -> f64 {
-    ((r as f64) * 256. + (g as f64) + (b as f64) / 255.0) - 32768.
+    ((r as f64) * 256. + (g as f64) + (b as f64) / 256.0) - 32768.
 }
 
 fn decode_mapbox_rgb(r: u16, g: u16, b: u16) -> f64 {
@@ -151,6 +336,8 @@ impl RasterValue {
             RasterValue::I16(v) => *v as f64,
             RasterValue::I32(v) => *v as f64,
             RasterValue::I64(v) => *v as f64,
+            RasterValue::Rgb32F(..) | RasterValue::Rgba32F(..) => f64::NAN,
+            RasterValue::CI16(..) | RasterValue::CI32(..) | RasterValue::CF32(..) => f64::NAN,
             RasterValue::NoData => f64::NAN,
         }
     }
@@ -172,9 +359,203 @@ impl RasterValue {
             RasterValue::I16(v) => *v as f64,
             RasterValue::I32(v) => *v as f64,
             RasterValue::I64(v) => *v as f64,
+            RasterValue::Rgb32F(..) | RasterValue::Rgba32F(..) => f64::NAN,
+            RasterValue::CI16(..) | RasterValue::CI32(..) | RasterValue::CF32(..) => f64::NAN,
             RasterValue::NoData => f64::NAN,
         }
     }
+
+    /// Sample type of single band values
+    pub fn sample_type(&self) -> Option<SampleType> {
+        match self {
+            RasterValue::U8(_) => Some(SampleType::U8),
+            RasterValue::U16(_) => Some(SampleType::U16),
+            RasterValue::U32(_) => Some(SampleType::U32),
+            RasterValue::U64(_) => Some(SampleType::U64),
+            RasterValue::F32(_) => Some(SampleType::F32),
+            RasterValue::F64(_) => Some(SampleType::F64),
+            RasterValue::I8(_) => Some(SampleType::I8),
+            RasterValue::I16(_) => Some(SampleType::I16),
+            RasterValue::I32(_) => Some(SampleType::I32),
+            RasterValue::I64(_) => Some(SampleType::I64),
+            _ => None,
+        }
+    }
+
+    /// Numeric value of single band values
+    ///
+    /// ```rust
+    /// use georaster::RasterValue;
+    ///
+    /// assert_eq!(RasterValue::I16(-7).as_f64(), Some(-7.0));
+    /// assert_eq!(RasterValue::Rgb8(1, 2, 3).as_f64(), None);
+    /// ```
+    pub fn as_f64(&self) -> Option<f64> {
+        f64::try_from(*self).ok()
+    }
+
+    /// Map value range `min..=max` linearly to `0.0..=1.0`
+    ///
+    /// Values outside the range are clamped.
+    pub fn normalize(&self, min: f64, max: f64) -> Option<f64> {
+        let v = (self.as_f64()? - min) / (max - min);
+        if v.is_nan() {
+            None
+        } else {
+            Some(v.clamp(0.0, 1.0))
+        }
+    }
+
+    /// Convert single band value into another sample type
+    ///
+    /// Values are rounded and saturated to the range of the target type, infinite
+    /// values and NaN are kept for float types.
+    /// Non-numeric values and NaN converted to integer types return `NoData`.
+    ///
+    /// ```rust
+    /// use georaster::{RasterValue, SampleType};
+    ///
+    /// assert_eq!(RasterValue::I16(-7).cast_to(SampleType::U8), RasterValue::U8(0));
+    /// assert_eq!(RasterValue::F32(300.6).cast_to(SampleType::U8), RasterValue::U8(255));
+    /// ```
+    pub fn cast_to(&self, sample_type: SampleType) -> RasterValue {
+        let Some(v) = self.as_f64() else {
+            return RasterValue::NoData;
+        };
+        let is_int = !matches!(sample_type, SampleType::F32 | SampleType::F64);
+        if v.is_nan() && is_int {
+            return RasterValue::NoData;
+        }
+        let v = if is_int { v.round() } else { v };
+        // `as` casts from float to integer saturate, but overflow to infinity for `f32`
+        match sample_type {
+            SampleType::U8 => RasterValue::U8(v as u8),
+            SampleType::U16 => RasterValue::U16(v as u16),
+            SampleType::U32 => RasterValue::U32(v as u32),
+            SampleType::U64 => RasterValue::U64(v as u64),
+            SampleType::F32 if v.is_finite() => {
+                RasterValue::F32(v.clamp(f32::MIN as f64, f32::MAX as f64) as f32)
+            }
+            SampleType::F32 => RasterValue::F32(v as f32),
+            SampleType::F64 => RasterValue::F64(v),
+            SampleType::I8 => RasterValue::I8(v as i8),
+            SampleType::I16 => RasterValue::I16(v as i16),
+            SampleType::I32 => RasterValue::I32(v as i32),
+            SampleType::I64 => RasterValue::I64(v as i64),
+        }
+    }
+
+    /// Convert single band value into another sample type without rounding or saturation
+    ///
+    /// Returns `GeorasterError::ValueRange` for non-numeric values and for values
+    /// which can't be represented in `sample_type`, like fractional, out of range
+    /// or NaN values for integer types. Float values may lose precision.
+    ///
+    /// ```rust
+    /// use georaster::{RasterValue, SampleType};
+    ///
+    /// assert_eq!(RasterValue::F64(7.0).try_cast_to(SampleType::U8).unwrap(), RasterValue::U8(7));
+    /// assert!(RasterValue::F64(-9999.0).try_cast_to(SampleType::U8).is_err());
+    /// ```
+    pub fn try_cast_to(&self, sample_type: SampleType) -> GeorasterResult<RasterValue> {
+        let v = self.as_f64().ok_or(GeorasterError::ValueRange)?;
+        let value = self.cast_to(sample_type);
+        let representable = match sample_type {
+            SampleType::F64 => true,
+            SampleType::F32 => !v.is_finite() || v.abs() <= f32::MAX as f64,
+            _ => value.as_f64() == Some(v),
+        };
+        if representable {
+            Ok(value)
+        } else {
+            Err(GeorasterError::ValueRange)
+        }
+    }
+
+    /// Apply scale and offset (`value * scale + offset`) to get physical values
+    ///
+    /// Non-numeric values return `NoData`.
+    pub fn scaled(&self, scale: f64, offset: f64) -> RasterValue {
+        match self.as_f64() {
+            Some(v) => RasterValue::F64(v * scale + offset),
+            None => RasterValue::NoData,
+        }
+    }
+
+    /// Amplitude of complex values or absolute value of real values
+    pub fn amplitude(&self) -> f64 {
+        match self.complex_parts() {
+            Some((re, im)) => re.hypot(im),
+            None => f64::NAN,
+        }
+    }
+
+    /// Phase of complex values in radians (`-π..=π`)
+    ///
+    /// Real values have a phase of 0 or π for negative values.
+    pub fn phase(&self) -> f64 {
+        match self.complex_parts() {
+            Some((re, im)) => im.atan2(re),
+            None => f64::NAN,
+        }
+    }
+
+    /// Real and imaginary part of single band values
+    fn complex_parts(&self) -> Option<(f64, f64)> {
+        match self {
+            RasterValue::CI16(re, im) => Some((*re as f64, *im as f64)),
+            RasterValue::CI32(re, im) => Some((*re as f64, *im as f64)),
+            RasterValue::CF32(re, im) => Some((*re as f64, *im as f64)),
+            value => value.as_f64().map(|v| (v, 0.0)),
+        }
+    }
+}
+
+/// Values of the same variant are ordered, different variants are not comparable.
+impl PartialOrd for RasterValue {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        match (self, other) {
+            (RasterValue::NoData, RasterValue::NoData) => Some(Ordering::Equal),
+            (RasterValue::U8(a), RasterValue::U8(b)) => a.partial_cmp(b),
+            (RasterValue::U16(a), RasterValue::U16(b)) => a.partial_cmp(b),
+            (RasterValue::U32(a), RasterValue::U32(b)) => a.partial_cmp(b),
+            (RasterValue::U64(a), RasterValue::U64(b)) => a.partial_cmp(b),
+            (RasterValue::F32(a), RasterValue::F32(b)) => a.partial_cmp(b),
+            (RasterValue::F64(a), RasterValue::F64(b)) => a.partial_cmp(b),
+            (RasterValue::I8(a), RasterValue::I8(b)) => a.partial_cmp(b),
+            (RasterValue::I16(a), RasterValue::I16(b)) => a.partial_cmp(b),
+            (RasterValue::I32(a), RasterValue::I32(b)) => a.partial_cmp(b),
+            (RasterValue::I64(a), RasterValue::I64(b)) => a.partial_cmp(b),
+            (RasterValue::Rgb8(r1, g1, b1), RasterValue::Rgb8(r2, g2, b2)) => {
+                (r1, g1, b1).partial_cmp(&(r2, g2, b2))
+            }
+            (RasterValue::Rgba8(r1, g1, b1, a1), RasterValue::Rgba8(r2, g2, b2, a2)) => {
+                (r1, g1, b1, a1).partial_cmp(&(r2, g2, b2, a2))
+            }
+            (RasterValue::Rgb16(r1, g1, b1), RasterValue::Rgb16(r2, g2, b2)) => {
+                (r1, g1, b1).partial_cmp(&(r2, g2, b2))
+            }
+            (RasterValue::Rgba16(r1, g1, b1, a1), RasterValue::Rgba16(r2, g2, b2, a2)) => {
+                (r1, g1, b1, a1).partial_cmp(&(r2, g2, b2, a2))
+            }
+            (RasterValue::Rgb32F(r1, g1, b1), RasterValue::Rgb32F(r2, g2, b2)) => {
+                (r1, g1, b1).partial_cmp(&(r2, g2, b2))
+            }
+            (RasterValue::Rgba32F(r1, g1, b1, a1), RasterValue::Rgba32F(r2, g2, b2, a2)) => {
+                (r1, g1, b1, a1).partial_cmp(&(r2, g2, b2, a2))
+            }
+            (RasterValue::CI16(re1, im1), RasterValue::CI16(re2, im2)) => {
+                (re1, im1).partial_cmp(&(re2, im2))
+            }
+            (RasterValue::CI32(re1, im1), RasterValue::CI32(re2, im2)) => {
+                (re1, im1).partial_cmp(&(re2, im2))
+            }
+            (RasterValue::CF32(re1, im1), RasterValue::CF32(re2, im2)) => {
+                (re1, im1).partial_cmp(&(re2, im2))
+            }
+            _ => None,
+        }
+    }
 }
 
 #[cfg(test)]
@@ -199,7 +580,161 @@ mod tests {
     fn height_conversion() {
         assert_eq!(RasterValue::U32(1243).height(), 1243.);
         assert_eq!(RasterValue::I64(-1).height(), -1.0);
-        assert_eq!(RasterValue::Rgb8(134, 65, 215).height(), 1601.843137254902);
+        assert_eq!(RasterValue::Rgb8(134, 65, 215).height(), 1601.83984375);
         assert!(RasterValue::NoData.height().is_nan());
+        assert!(RasterValue::CI16(3, 4).height().is_nan());
+    }
+
+    #[test]
+    fn elevation_encoding() {
+        for height in [-413.0, 0.0, 1607.5, 4807.7] {
+            let [r, g, b] = ElevationEncoding::Terrarium.encode_rgb(height).unwrap();
+            // Lossless up to the 1/256 resolution of the blue channel
+            let expected = (height * 256.0).floor() / 256.0;
+            assert_eq!(RasterValue::Rgb8(r, g, b).height(), expected);
+            assert_eq!(
+                ElevationEncoding::Terrarium.decode(RasterValue::Rgb8(r, g, b)),
+                Some(expected)
+            );
+            let [r, g, b] = ElevationEncoding::Mapbox.encode_rgb(height).unwrap();
+            assert!((RasterValue::Rgb8(r, g, b).height_mb() - height).abs() < 0.1);
+        }
+        assert_eq!(ElevationEncoding::Raw.encode_rgb(1.0), None);
+        assert_eq!(
+            ElevationEncoding::Terrarium.decode(RasterValue::Rgba8(128, 0, 0, 255)),
+            Some(0.0)
+        );
+        assert_eq!(
+            ElevationEncoding::Terrarium.decode(RasterValue::Rgb8(0, 0, 0)),
+            None
+        );
+        assert_eq!(
+            ElevationEncoding::Raw.decode(RasterValue::U16(412)),
+            Some(412.0)
+        );
+        assert_eq!(
+            ElevationEncoding::Raw.decode(RasterValue::F32(f32::NAN)),
+            None
+        );
+        assert_eq!(ElevationEncoding::Raw.decode(RasterValue::NoData), None);
+        assert_eq!(
+            "Terrarium".parse::<ElevationEncoding>().unwrap(),
+            ElevationEncoding::Terrarium
+        );
+        assert_eq!(ElevationEncoding::Mapbox.to_string(), "mapbox");
+        assert!("png".parse::<ElevationEncoding>().is_err());
+    }
+
+    #[test]
+    fn value_helpers() {
+        assert_eq!(RasterValue::U16(300).as_f64(), Some(300.0));
+        assert_eq!(RasterValue::NoData.as_f64(), None);
+        assert_eq!(RasterValue::U8(51).normalize(0.0, 255.0), Some(0.2));
+        assert_eq!(RasterValue::I16(-10).normalize(0.0, 100.0), Some(0.0));
+        assert_eq!(RasterValue::F32(5.0).normalize(5.0, 5.0), None);
+        assert_eq!(RasterValue::U8(1).sample_type(), Some(SampleType::U8));
+        assert_eq!(RasterValue::Rgb8(1, 2, 3).sample_type(), None);
+
+        assert_eq!(
+            RasterValue::I32(70000).cast_to(SampleType::I16),
+            RasterValue::I16(i16::MAX)
+        );
+        assert_eq!(
+            RasterValue::F64(-1.5).cast_to(SampleType::I8),
+            RasterValue::I8(-2)
+        );
+        assert_eq!(
+            RasterValue::U8(7).cast_to(SampleType::F32),
+            RasterValue::F32(7.0)
+        );
+        assert_eq!(
+            RasterValue::F64(1e300).cast_to(SampleType::F32),
+            RasterValue::F32(f32::MAX)
+        );
+        assert_eq!(
+            RasterValue::F64(-1e300).cast_to(SampleType::F32),
+            RasterValue::F32(f32::MIN)
+        );
+        assert_eq!(
+            RasterValue::F64(f64::NEG_INFINITY).cast_to(SampleType::F32),
+            RasterValue::F32(f32::NEG_INFINITY)
+        );
+        assert_eq!(
+            RasterValue::F32(f32::NAN).cast_to(SampleType::U16),
+            RasterValue::NoData
+        );
+        assert_eq!(
+            RasterValue::Rgb8(1, 2, 3).cast_to(SampleType::U8),
+            RasterValue::NoData
+        );
+        assert_eq!(
+            RasterValue::F64(-9999.0)
+                .try_cast_to(SampleType::I16)
+                .unwrap(),
+            RasterValue::I16(-9999)
+        );
+        assert!(RasterValue::I32(70000)
+            .try_cast_to(SampleType::I16)
+            .is_err());
+        assert!(RasterValue::F64(-1.5).try_cast_to(SampleType::I8).is_err());
+        assert!(RasterValue::F64(f64::NAN)
+            .try_cast_to(SampleType::U8)
+            .is_err());
+        assert!(RasterValue::F64(f64::NAN)
+            .try_cast_to(SampleType::F32)
+            .is_ok());
+        assert!(RasterValue::F64(1e300)
+            .try_cast_to(SampleType::F32)
+            .is_err());
+        assert!(RasterValue::F64(f64::INFINITY)
+            .try_cast_to(SampleType::F32)
+            .is_ok());
+        assert!(RasterValue::Rgb8(1, 2, 3)
+            .try_cast_to(SampleType::U8)
+            .is_err());
+
+        assert_eq!(
+            RasterValue::I16(250).scaled(0.5, 10.0),
+            RasterValue::F64(135.0)
+        );
+        assert_eq!(RasterValue::NoData.scaled(0.1, 0.0), RasterValue::NoData);
+    }
+
+    #[test]
+    fn value_ordering() {
+        assert!(RasterValue::U8(1) < RasterValue::U8(2));
+        assert!(RasterValue::F32(-1.0) < RasterValue::F32(0.5));
+        assert!(RasterValue::Rgb8(1, 2, 3) < RasterValue::Rgb8(1, 3, 0));
+        assert_eq!(RasterValue::U8(1).partial_cmp(&RasterValue::U16(2)), None);
+        assert_eq!(
+            RasterValue::F32(f32::NAN).partial_cmp(&RasterValue::F32(1.0)),
+            None
+        );
+        assert_eq!(
+            [
+                RasterValue::I16(5),
+                RasterValue::I16(-3),
+                RasterValue::I16(9)
+            ]
+            .into_iter()
+            .max_by(|a, b| a.partial_cmp(b).unwrap()),
+            Some(RasterValue::I16(9))
+        );
+    }
+
+    #[test]
+    fn complex_values() {
+        assert_eq!(RasterValue::CI16(3, 4).amplitude(), 5.0);
+        assert_eq!(
+            RasterValue::CF32(0.0, 2.0).phase(),
+            std::f64::consts::FRAC_PI_2
+        );
+        assert_eq!(RasterValue::CI32(-1, 0).phase(), std::f64::consts::PI);
+        assert_eq!(RasterValue::I16(-7).amplitude(), 7.0);
+        assert_eq!(RasterValue::I16(-7).phase(), std::f64::consts::PI);
+        assert_eq!(RasterValue::U8(7).phase(), 0.0);
+        assert!(RasterValue::Rgb8(1, 2, 3).amplitude().is_nan());
+        assert_eq!(RasterValue::CI16(3, -4).to_string(), "3-4i");
+        assert_eq!(f64::try_from(RasterValue::CF32(1.0, 1.0)).ok(), None);
     }
 }
@@ -0,0 +1,197 @@
+//! GDAL metadata stored in GeoTIFF files.
+// GDAL_METADATA tag: https://gdal.org/drivers/raster/gtiff.html#metadata
+
+use std::collections::BTreeMap;
+
+/// Metadata from the `GDAL_METADATA` TIFF tag (42112)
+///
+/// ```rust
+/// use georaster::metadata::GdalMetadata;
+///
+/// let xml = r#"<GDALMetadata>
+///   <Item name="AREA_OR_POINT">Area</Item>
+///   <Item name="SCALE" sample="0" role="scale">0.01</Item>
+/// </GDALMetadata>"#;
+/// let metadata = GdalMetadata::parse(xml);
+/// assert_eq!(metadata.items.get("AREA_OR_POINT").unwrap(), "Area");
+/// assert_eq!(metadata.band(1).unwrap().scale, Some(0.01));
+/// ```
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct GdalMetadata {
+    /// Dataset metadata items
+    pub items: BTreeMap<String, String>,
+    /// Band metadata (index 0 = band 1)
+    pub bands: Vec<BandMetadata>,
+}
+
+/// Band metadata
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct BandMetadata {
+    /// Scale for computing physical values (`value * scale + offset`)
+    pub scale: Option<f64>,
+    /// Offset for computing physical values (`value * scale + offset`)
+    pub offset: Option<f64>,
+    /// Unit of physical values
+    pub unit_type: Option<String>,
+    pub description: Option<String>,
+    /// Statistics computed by GDAL
+    pub statistics: BandStatistics,
+    /// Other band metadata items
+    pub items: BTreeMap<String, String>,
+}
+
+/// Band statistics from `STATISTICS_*` items
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct BandStatistics {
+    pub minimum: Option<f64>,
+    pub maximum: Option<f64>,
+    pub mean: Option<f64>,
+    pub stddev: Option<f64>,
+    pub valid_percent: Option<f64>,
+}
+
+impl GdalMetadata {
+    /// Parse `GDALMetadata` XML
+    ///
+    /// Items of non-default metadata domains and unparsable items are ignored.
+    pub fn parse(xml: &str) -> Self {
+        let mut metadata = GdalMetadata::default();
+        let mut rest = xml;
+        while let Some(start) = rest.find("<Item") {
+            rest = &rest[start + 5..];
+            let Some(tag_end) = rest.find('>') else {
+                break;
+            };
+            let attrs = &rest[..tag_end];
+            rest = &rest[tag_end + 1..];
+            if attrs.ends_with('/') {
+                // Empty element
+                continue;
+            }
+            let Some(end) = rest.find("</Item>") else {
+                break;
+            };
+            let value = unescape(rest[..end].trim());
+            rest = &rest[end + 7..];
+            if attribute(attrs, "domain").is_some_and(|domain| !domain.is_empty()) {
+                continue;
+            }
+            let Some(name) = attribute(attrs, "name") else {
+                continue;
+            };
+            match attribute(attrs, "sample").and_then(|s| s.parse::<usize>().ok()) {
+                Some(sample) => {
+                    if metadata.bands.len() <= sample {
+                        metadata.bands.resize(sample + 1, BandMetadata::default());
+                    }
+                    let role = attribute(attrs, "role").unwrap_or_default();
+                    metadata.bands[sample].set_item(&name, &role, value);
+                }
+                None => {
+                    metadata.items.insert(name, value);
+                }
+            }
+        }
+        metadata
+    }
+
+    /// Metadata of band (starting with 1)
+    pub fn band(&self, band: u8) -> Option<&BandMetadata> {
+        self.bands.get((band as usize).checked_sub(1)?)
+    }
+}
+
+impl BandMetadata {
+    fn set_item(&mut self, name: &str, role: &str, value: String) {
+        let number = value.parse::<f64>().ok();
+        match (role, name) {
+            ("scale", _) => self.scale = number,
+            ("offset", _) => self.offset = number,
+            ("unittype", _) => self.unit_type = Some(value),
+            ("description", _) => self.description = Some(value),
+            (_, "STATISTICS_MINIMUM") => self.statistics.minimum = number,
+            (_, "STATISTICS_MAXIMUM") => self.statistics.maximum = number,
+            (_, "STATISTICS_MEAN") => self.statistics.mean = number,
+            (_, "STATISTICS_STDDEV") => self.statistics.stddev = number,
+            (_, "STATISTICS_VALID_PERCENT") => self.statistics.valid_percent = number,
+            _ => {
+                self.items.insert(name.to_string(), value);
+            }
+        }
+    }
+
+    /// Scale and offset, if at least one of them is defined
+    pub fn scale_offset(&self) -> Option<(f64, f64)> {
+        if self.scale.is_none() && self.offset.is_none() {
+            return None;
+        }
+        Some((self.scale.unwrap_or(1.0), self.offset.unwrap_or(0.0)))
+    }
+}
+
+/// Value of XML attribute `name="value"`
+fn attribute(attrs: &str, name: &str) -> Option<String> {
+    let mut rest = attrs;
+    loop {
+        let pos = rest.find(name)?;
+        let preceded_by_space = rest[..pos].chars().last().is_none_or(char::is_whitespace);
+        rest = &rest[pos + name.len()..];
+        let after = rest.trim_start();
+        if preceded_by_space && after.starts_with('=') {
+            let after = after[1..].trim_start();
+            let quote = after.chars().next()?;
+            if quote != '"' && quote != '\'' {
+                return None;
+            }
+            let end = after[1..].find(quote)?;
+            return Some(unescape(&after[1..end + 1]));
+        }
+    }
+}
+
+fn unescape(text: &str) -> String {
+    text.replace("&lt;", "<")
+        .replace("&gt;", ">")
+        .replace("&quot;", "\"")
+        .replace("&apos;", "'")
+        .replace("&amp;", "&")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_band_metadata() {
+        let xml = r#"<GDALMetadata>
+  <Item name="TITLE">Temperature &amp; more</Item>
+  <Item name="OFFSET" sample="1" role="offset">-273.15</Item>
+  <Item name="SCALE" sample="1" role="scale">0.01</Item>
+  <Item name="UNITTYPE" sample="1" role="unittype">deg C</Item>
+  <Item name="STATISTICS_MEAN" sample="1">28215</Item>
+  <Item name="COLORINTERP" sample="1">Gray</Item>
+  <Item name="Other" domain="IMAGERY">ignored</Item>
+</GDALMetadata>"#;
+        let metadata = GdalMetadata::parse(xml);
+        assert_eq!(metadata.items.len(), 1);
+        assert_eq!(metadata.items["TITLE"], "Temperature & more");
+        assert_eq!(metadata.bands.len(), 2);
+        assert_eq!(metadata.band(1), Some(&BandMetadata::default()));
+        let band = metadata.band(2).unwrap();
+        assert_eq!(band.scale_offset(), Some((0.01, -273.15)));
+        assert_eq!(band.unit_type.as_deref(), Some("deg C"));
+        assert_eq!(band.statistics.mean, Some(28215.0));
+        assert_eq!(band.items["COLORINTERP"], "Gray");
+        assert_eq!(metadata.band(0), None);
+        assert_eq!(metadata.band(3), None);
+    }
+
+    #[test]
+    fn parse_invalid() {
+        assert_eq!(GdalMetadata::parse(""), GdalMetadata::default());
+        assert_eq!(
+            GdalMetadata::parse("<GDALMetadata><Item name=\"A\">1"),
+            GdalMetadata::default()
+        );
+    }
+}
@@ -1,24 +1,249 @@
-use crate::{Coordinate, GeorasterResult, RasterValue};
-use image::{DynamicImage, GenericImageView, ImageReader, Pixel};
-use pmt::{AsyncPmTilesReader, PmtError, TileCoord};
+use crate::geo;
+use crate::geotiff::GeoTiffReader;
+use crate::profile::{self, Profile, ProfilePoint, ProfileStep};
+use crate::viewshed::{self, LineOfSight, Observer};
+use crate::{
+    AsyncGeoRaster, BoundingBox, Coordinate, ElevationEncoding, GeorasterError, GeorasterResult,
+    Raster, RasterValue,
+};
+use bytes::Bytes;
+use image::error::{UnsupportedError, UnsupportedErrorKind};
+use image::{DynamicImage, GenericImageView, ImageError, ImageFormat, ImageReader, Pixel};
+use lru::LruCache;
+use pmt::{AsyncBackend, AsyncPmTilesReader, PmtError, PmtResult, TileCoord};
+use std::collections::BTreeMap;
 use std::io::Cursor;
-use tile_grid::{tms, BoundingBox, Xyz};
+use std::num::NonZeroUsize;
+use std::sync::{Arc, Mutex};
+use tile_grid::{tms, TileMatrixSetOps, TileMatrixSets, Tms, Xyz};
+
+pub use pmt::{Compression, Header, TileType};
+
+/// Default number of decoded tiles kept in memory
+const DEFAULT_CACHE_SIZE: usize = 64;
+
+/// Default maximal number of pixels of rasters read from multiple tiles
+const DEFAULT_MAX_PIXELS: u64 = 4096 * 4096;
+
+/// Tile cache key (z, x, y)
+type TileKey = (u8, u64, u64);
 
 /// PMTiles raster reader
-pub struct PmtilesRasterReader {
-    reader: AsyncPmTilesReader<pmt::MmapBackend>,
+///
+/// Generic over the storage backend of the archive. Supported backends are
+/// memory mapped files, in-memory bytes, HTTP (feature `pmtiles-http`),
+/// S3 (feature `pmtiles-s3`) and any user-supplied `pmt::AsyncBackend`.
+pub struct PmtilesRasterReader<B = pmt::MmapBackend> {
+    reader: AsyncPmTilesReader<B>,
     tms: tile_grid::Tms,
+    /// LRU cache of decoded tiles
+    cache: Option<Mutex<LruCache<TileKey, Arc<Tile>>>>,
+    encoding: ElevationEncoding,
+    /// Archive metadata
+    metadata: serde_json::Value,
+    /// Maximal number of pixels of mosaic rasters
+    max_pixels: u64,
 }
 
-impl PmtilesRasterReader {
+impl PmtilesRasterReader<pmt::MmapBackend> {
+    /// Open local PMTiles file
     pub async fn open(name: &str) -> GeorasterResult<Self> {
-        let tms = tms().lookup("WebMercatorQuad").unwrap();
         // Use `new_with_cached_path` for better performance
-        let reader = AsyncPmTilesReader::new_with_path(name).await?;
-        Ok(Self { reader, tms })
+        let backend = pmt::MmapBackend::try_from(name).await?;
+        Self::from_backend(backend).await
+    }
+}
+
+impl PmtilesRasterReader<MemoryBackend> {
+    /// Read PMTiles archive from memory
+    pub async fn from_bytes(data: impl Into<Bytes>) -> GeorasterResult<Self> {
+        Self::from_backend(MemoryBackend(data.into())).await
+    }
+}
+
+#[cfg(feature = "pmtiles-http")]
+impl PmtilesRasterReader<pmt::HttpBackend> {
+    /// Open PMTiles archive with HTTP range requests
+    pub async fn open_url(url: &str) -> GeorasterResult<Self> {
+        Self::open_url_with_client(pmt::reqwest::Client::new(), url).await
+    }
+
+    /// Open PMTiles archive with HTTP range requests using a custom client
+    pub async fn open_url_with_client(
+        client: pmt::reqwest::Client,
+        url: &str,
+    ) -> GeorasterResult<Self> {
+        let backend = pmt::HttpBackend::try_from(client, url)?;
+        Self::from_backend(backend).await
+    }
+}
+
+#[cfg(feature = "pmtiles-s3")]
+impl PmtilesRasterReader<pmt::S3Backend> {
+    /// Open PMTiles archive in S3-compatible object storage
+    pub async fn open_s3(bucket: pmt::s3::Bucket, path: &str) -> GeorasterResult<Self> {
+        let backend = pmt::S3Backend::from(bucket, path.to_string());
+        Self::from_backend(backend).await
+    }
+}
+
+impl<B: AsyncBackend + Sync + Send> PmtilesRasterReader<B> {
+    /// Read PMTiles archive from backend
+    pub async fn from_backend(backend: B) -> GeorasterResult<Self> {
+        let reader = AsyncPmTilesReader::try_from_source(backend).await?;
+        let cache =
+            NonZeroUsize::new(DEFAULT_CACHE_SIZE).map(|size| Mutex::new(LruCache::new(size)));
+        let metadata: serde_json::Value =
+            serde_json::from_str(&reader.get_metadata().await?).unwrap_or_default();
+        let encoding = metadata_encoding(&metadata).unwrap_or(ElevationEncoding::Terrarium);
+        let tms = match metadata_tms(&metadata)? {
+            Some(tms) => tms,
+            None => tms().lookup("WebMercatorQuad")?,
+        };
+        Ok(Self {
+            reader,
+            tms,
+            cache,
+            encoding,
+            metadata,
+            max_pixels: DEFAULT_MAX_PIXELS,
+        })
+    }
+
+    /// Set tile matrix set of tiles
+    ///
+    /// By default, the `tile_matrix_set` entry of the archive metadata is used,
+    /// which is either an identifier of the `tile-grid` registry or a
+    /// TileMatrixSet JSON object. Without metadata entry, `WebMercatorQuad` is used.
+    /// Tiles are addressed with their TMS column and row, which limits the
+    /// archive to tiles within the quadtree of the PMTiles tile ids.
+    pub fn with_tms(mut self, tms: Tms) -> Self {
+        self.tms = tms;
+        self
+    }
+
+    /// Set tile matrix set from `tile-grid` registry, e.g. `WorldCRS84Quad`
+    pub fn with_tms_id(self, id: &str) -> GeorasterResult<Self> {
+        let tms = tms().lookup(id)?;
+        Ok(self.with_tms(tms))
+    }
+
+    /// Tile matrix set of tiles
+    pub fn tms(&self) -> &Tms {
+        &self.tms
+    }
+
+    /// Archive header
+    pub fn header(&self) -> &Header {
+        self.reader.get_header()
+    }
+
+    /// Archive JSON metadata
+    pub fn metadata(&self) -> &serde_json::Value {
+        &self.metadata
+    }
+
+    /// Minimum zoom level of archive
+    pub fn min_zoom(&self) -> u8 {
+        self.header().min_zoom
+    }
+
+    /// Maximum zoom level of archive
+    pub fn max_zoom(&self) -> u8 {
+        self.header().max_zoom
     }
 
+    /// Archive bounds in WGS84 longitude/latitude
+    pub fn bounds(&self) -> BoundingBox {
+        let header = self.header();
+        BoundingBox::new(
+            header.min_longitude as f64,
+            header.min_latitude as f64,
+            header.max_longitude as f64,
+            header.max_latitude as f64,
+        )
+        .with_epsg(4326)
+    }
+
+    /// Center in WGS84 longitude/latitude
+    pub fn center(&self) -> Coordinate {
+        let header = self.header();
+        Coordinate {
+            x: header.center_longitude as f64,
+            y: header.center_latitude as f64,
+        }
+    }
+
+    /// Zoom level of center
+    pub fn center_zoom(&self) -> u8 {
+        self.header().center_zoom
+    }
+
+    /// Type of tiles
+    pub fn tile_type(&self) -> TileType {
+        self.header().tile_type
+    }
+
+    /// Compression of tiles
+    pub fn tile_compression(&self) -> Compression {
+        self.header().tile_compression
+    }
+
+    /// Set elevation encoding of tiles
+    ///
+    /// By default, the `encoding` entry of the archive metadata is used,
+    /// falling back to `Terrarium`.
+    pub fn with_encoding(mut self, encoding: ElevationEncoding) -> Self {
+        self.encoding = encoding;
+        self
+    }
+
+    /// Elevation encoding of tiles
+    pub fn encoding(&self) -> ElevationEncoding {
+        self.encoding
+    }
+
+    /// Set number of decoded tiles kept in memory (0 disables caching)
+    pub fn with_cache_size(mut self, size: usize) -> Self {
+        self.cache = NonZeroUsize::new(size).map(|size| Mutex::new(LruCache::new(size)));
+        self
+    }
+
+    /// Set maximal number of pixels of rasters read from multiple tiles
+    ///
+    /// Reading larger windows with `read_bbox`, `read_heights`, `line_of_sight`,
+    /// `viewshed` or `AsyncGeoRaster::read_extent` returns
+    /// `GeorasterError::WindowTooLarge`. The default is 4096 x 4096 pixels.
+    pub fn with_max_pixels(mut self, max_pixels: u64) -> Self {
+        self.max_pixels = max_pixels;
+        self
+    }
+
+    /// Decoded image tile
+    ///
+    /// TIFF tiles, which are decoded into raster values, are not supported.
     pub async fn get_tile(&self, xyz: &Xyz) -> GeorasterResult<DynamicImage> {
+        match &*self.get_cached_tile(xyz).await? {
+            Tile::Image(img) => Ok(img.clone()),
+            Tile::Raster { .. } => Err(ImageError::Unsupported(
+                UnsupportedError::from_format_and_kind(
+                    ImageFormat::Tiff.into(),
+                    UnsupportedErrorKind::GenericFeature("raster value tiles".to_string()),
+                ),
+            )
+            .into()),
+        }
+    }
+
+    /// Decoded tile from cache or archive
+    async fn get_cached_tile(&self, xyz: &Xyz) -> GeorasterResult<Arc<Tile>> {
+        let key = (xyz.z, xyz.x, xyz.y);
+        if let Some(cache) = &self.cache {
+            if let Some(tile) = cache.lock().unwrap().get(&key) {
+                return Ok(tile.clone());
+            }
+        }
+
         let coord =
             TileCoord::new(xyz.z, xyz.x as u32, xyz.y as u32).ok_or(PmtError::InvalidEntry)?;
         let bytes = self
@@ -27,32 +252,430 @@ impl PmtilesRasterReader {
             .await?
             .ok_or(PmtError::InvalidEntry)?;
 
-        let img = ImageReader::new(Cursor::new(bytes))
-            .with_guessed_format()?
-            .decode()?;
-        Ok(img)
+        let tile = Arc::new(Tile::decode(bytes)?);
+        if let Some(cache) = &self.cache {
+            cache.lock().unwrap().put(key, tile.clone());
+        }
+        Ok(tile)
+    }
+
+    /// Decoded tile from cache or archive, `None` for missing tiles
+    async fn get_optional_tile(&self, xyz: &Xyz) -> GeorasterResult<Option<Arc<Tile>>> {
+        match self.get_cached_tile(xyz).await {
+            Ok(tile) => Ok(Some(tile)),
+            Err(GeorasterError::Pmt(PmtError::InvalidEntry)) => Ok(None),
+            Err(e) => Err(e),
+        }
     }
 
     /// Return raster value at geographical location
+    ///
+    /// `coord` is a WGS84 longitude/latitude in degrees, which is projected
+    /// into the CRS of the tile matrix set. Locations in missing tiles
+    /// return `RasterValue::NoData`.
     pub async fn get_pixel_at(
         &self,
         z: u8,
         coord: impl Into<Coordinate>,
+    ) -> GeorasterResult<RasterValue> {
+        let xy = self.project(coord.into())?;
+        self.get_pixel_at_xy(z, xy).await
+    }
+
+    /// Return raster value at location in the CRS of the tile matrix set
+    ///
+    /// Locations in missing tiles return `RasterValue::NoData`.
+    pub async fn get_pixel_at_xy(
+        &self,
+        z: u8,
+        xy: impl Into<Coordinate>,
+    ) -> GeorasterResult<RasterValue> {
+        let xy = xy.into();
+        let xyz = self.tms.xy_tile(xy.x, xy.y, z);
+        let value = self
+            .get_optional_tile(&xyz)
+            .await?
+            .and_then(|tile| self.tile_pixel(&tile, &xyz, xy));
+        Ok(value.unwrap_or(RasterValue::NoData))
+    }
+
+    /// Return raster value at geographical location from the most detailed tile
+    ///
+    /// Starts at the maximum zoom level of the archive and falls back to
+    /// parent tiles when a tile is missing. Locations outside of the archive
+    /// bounds return `RasterValue::NoData`.
+    pub async fn get_pixel_at_best(
+        &self,
+        coord: impl Into<Coordinate>,
     ) -> GeorasterResult<RasterValue> {
         let coord = coord.into();
-        let xyz = self.tms.tile(coord.x, coord.y, z)?;
-        let tile = self.get_tile(&xyz).await?;
-        let bounds = self.tms.bounds(&xyz)?;
-        if let Some((px, py)) = self.coord_to_pixel(&bounds, coord, tile.width(), tile.height()) {
-            Ok(tile.get_pixel(px, py).into())
-        } else {
-            Err(PmtError::InvalidEntry.into())
+        let bounds = self.bounds();
+        let in_bounds = (bounds.left..=bounds.right).contains(&coord.x)
+            && (bounds.bottom..=bounds.top).contains(&coord.y);
+        if !in_bounds {
+            return Ok(RasterValue::NoData);
         }
+        self.best_pixel_xy(self.project(coord)?).await
+    }
+
+    /// Raster value at location in TMS CRS from the most detailed tile
+    async fn best_pixel_xy(&self, xy: Coordinate) -> GeorasterResult<RasterValue> {
+        for z in (self.min_zoom()..=self.max_zoom()).rev() {
+            let xyz = self.tms.xy_tile(xy.x, xy.y, z);
+            if let Some(tile) = self.get_optional_tile(&xyz).await? {
+                return Ok(self
+                    .tile_pixel(&tile, &xyz, xy)
+                    .unwrap_or(RasterValue::NoData));
+            }
+        }
+        Ok(RasterValue::NoData)
+    }
+
+    /// Return height in meters at geographical location
+    ///
+    /// Decodes the raster value with the elevation encoding of the archive.
+    /// Returns NaN for NoData values.
+    pub async fn get_height_at(&self, z: u8, coord: impl Into<Coordinate>) -> GeorasterResult<f64> {
+        let value = self.get_pixel_at(z, coord).await?;
+        Ok(self.encoding.decode(value).unwrap_or(f64::NAN))
     }
 
+    /// Return raster values at geographical locations
+    ///
+    /// Points are grouped by tile, so that each tile is fetched only once.
+    /// Points in missing tiles return `RasterValue::NoData`.
+    pub async fn get_pixels_at(
+        &self,
+        z: u8,
+        coords: &[Coordinate],
+    ) -> GeorasterResult<Vec<RasterValue>> {
+        let mut values = vec![RasterValue::NoData; coords.len()];
+        let mut tiles: BTreeMap<(u64, u64), Vec<(usize, Coordinate)>> = BTreeMap::new();
+        for (idx, coord) in coords.iter().enumerate() {
+            let (xyz, xy) = self.tile_at(*coord, z)?;
+            tiles.entry((xyz.x, xyz.y)).or_default().push((idx, xy));
+        }
+        for ((x, y), points) in tiles {
+            let xyz = Xyz::new(x, y, z);
+            let Some(tile) = self.get_optional_tile(&xyz).await? else {
+                continue;
+            };
+            for (idx, xy) in points {
+                values[idx] = self
+                    .tile_pixel(&tile, &xyz, xy)
+                    .unwrap_or(RasterValue::NoData);
+            }
+        }
+        Ok(values)
+    }
+
+    /// Read all tiles covering a bounding box into one raster
+    ///
+    /// `bbox` is in WGS84 longitude/latitude, the geotransform of the returned
+    /// raster is in the CRS of the tile matrix set. Missing tiles are filled
+    /// with `RasterValue::NoData`. Windows larger than the limit set with
+    /// `with_max_pixels` return an error.
+    pub async fn read_bbox(&self, bbox: &BoundingBox, z: u8) -> GeorasterResult<Raster> {
+        let ll = self.project(Coordinate {
+            x: bbox.left,
+            y: bbox.bottom,
+        })?;
+        let ur = self.project(Coordinate {
+            x: bbox.right,
+            y: bbox.top,
+        })?;
+        self.read_xy_window(ll, ur, z).await
+    }
+
+    /// Read heights of all tiles covering a bounding box into one raster
+    ///
+    /// Like `read_bbox`, with values decoded into `RasterValue::F64` heights
+    /// with the elevation encoding of the archive.
+    pub async fn read_heights(&self, bbox: &BoundingBox, z: u8) -> GeorasterResult<Raster> {
+        let mut raster = self.read_bbox(bbox, z).await?;
+        self.decode_heights(&mut raster);
+        Ok(raster)
+    }
+
+    fn decode_heights(&self, raster: &mut Raster) {
+        for value in raster.values.iter_mut() {
+            *value = match self.encoding.decode(*value) {
+                Some(height) => RasterValue::F64(height),
+                None => RasterValue::NoData,
+            };
+        }
+    }
+
+    /// Heights along a polyline
+    ///
+    /// Coordinates are WGS84 longitude/latitude, distances in metres. Heights
+    /// are decoded with the elevation encoding of the archive and bilinear
+    /// interpolated at the maximal zoom level. Long lines are read in windows
+    /// within the limit set with `with_max_pixels`.
+    pub async fn profile(
+        &self,
+        line: &[Coordinate],
+        step: ProfileStep,
+    ) -> GeorasterResult<Profile> {
+        let Some(start) = line.first() else {
+            return Ok(Profile::default());
+        };
+        let z = self.max_zoom();
+        let matrix = self.tms.matrix(z);
+        let matrix = matrix.as_ref();
+        // Pixel grid of the tile at the start of the line
+        let (xyz, _) = self.tile_at(*start, z)?;
+        let bounds = self.tms.xy_bounds(&xyz);
+        let pixel_size = [
+            (bounds.right - bounds.left) / u16::from(matrix.tile_width) as f64,
+            -(bounds.top - bounds.bottom) / u16::from(matrix.tile_height) as f64,
+        ];
+        let origin = [bounds.left, bounds.top];
+        let segments = profile::sample_positions(line, step, true, origin, pixel_size, |coord| {
+            self.project(coord)
+        })?;
+        let buffer = pixel_size[0].abs().max(pixel_size[1].abs());
+        // Window size in pixels within the pixel limit, including buffer and rounding
+        let window = ((self.max_pixels as f64).sqrt() - 4.0).max(0.0);
+        let mut profile = Profile::default();
+        for samples in segments {
+            let xy = samples
+                .iter()
+                .map(|(_, coord)| self.project(*coord))
+                .collect::<GeorasterResult<Vec<_>>>()?;
+            // Read consecutive samples in windows of bounded size
+            let mut start = 0;
+            while start < xy.len() {
+                let mut bbox = BoundingBox::new(xy[start].x, xy[start].y, xy[start].x, xy[start].y);
+                let mut end = start + 1;
+                while let Some(next) = xy.get(end) {
+                    let extended = bbox.union(&BoundingBox::new(next.x, next.y, next.x, next.y));
+                    if extended.width().max(extended.height()) > window * buffer {
+                        break;
+                    }
+                    bbox = extended;
+                    end += 1;
+                }
+                let bbox = bbox.buffer(buffer);
+                let mut raster = self
+                    .read_xy_window(
+                        Coordinate {
+                            x: bbox.left,
+                            y: bbox.bottom,
+                        },
+                        Coordinate {
+                            x: bbox.right,
+                            y: bbox.top,
+                        },
+                        z,
+                    )
+                    .await?;
+                self.decode_heights(&mut raster);
+                profile
+                    .points
+                    .extend(samples[start..end].iter().zip(&xy[start..end]).map(
+                        |((distance, coord), xy)| ProfilePoint {
+                            distance: *distance,
+                            coord: *coord,
+                            value: raster.interpolate(*xy),
+                        },
+                    ));
+                start = end;
+            }
+        }
+        Ok(profile)
+    }
+
+    /// Line of sight between WGS84 locations `from` and `to`
+    ///
+    /// See `viewshed::line_of_sight`, heights are decoded at the maximal zoom level
+    /// and distances are in metres.
+    pub async fn line_of_sight(
+        &self,
+        from: Coordinate,
+        to: Coordinate,
+        observer: &Observer,
+    ) -> GeorasterResult<Option<LineOfSight>> {
+        let (from, to) = (self.project(from)?, self.project(to)?);
+        let Some(bbox) = BoundingBox::from_coords([from, to]) else {
+            return Ok(None);
+        };
+        let dem = self.read_xy_heights(&bbox).await?;
+        Ok(viewshed::line_of_sight(
+            &dem,
+            AsyncGeoRaster::epsg_code(self),
+            from,
+            to,
+            observer,
+        ))
+    }
+
+    /// Visible area within `radius` metres around the WGS84 location `center`
+    ///
+    /// See `viewshed::viewshed`, heights are decoded at the maximal zoom level.
+    /// The returned raster is in TMS CRS and covers the tiles of the analysed area.
+    pub async fn viewshed(
+        &self,
+        center: Coordinate,
+        radius: f64,
+        observer: &Observer,
+    ) -> GeorasterResult<Raster> {
+        let center = self.project(center)?;
+        let epsg = AsyncGeoRaster::epsg_code(self);
+        let (scale_x, scale_y) = geo::ground_scale(epsg, center.y);
+        let bbox = BoundingBox::new(
+            center.x - radius / scale_x,
+            center.y - radius / scale_y,
+            center.x + radius / scale_x,
+            center.y + radius / scale_y,
+        );
+        let dem = self.read_xy_heights(&bbox).await?;
+        Ok(viewshed::viewshed(&dem, epsg, center, radius, observer))
+    }
+
+    /// Heights at maximal zoom level of `bbox` in TMS CRS with a margin of one pixel
+    ///
+    /// Returns `GeorasterError::WindowTooLarge` if the window exceeds `max_pixels`
+    /// at the maximal zoom level.
+    async fn read_xy_heights(&self, bbox: &BoundingBox) -> GeorasterResult<Raster> {
+        let resolution = AsyncGeoRaster::resolution(self).ok_or(GeorasterError::NoGeoreference)?;
+        let bbox = bbox.buffer(resolution[0].max(resolution[1]));
+        let mut raster = self
+            .read_xy_window(
+                Coordinate {
+                    x: bbox.left,
+                    y: bbox.bottom,
+                },
+                Coordinate {
+                    x: bbox.right,
+                    y: bbox.top,
+                },
+                self.max_zoom(),
+            )
+            .await?;
+        self.decode_heights(&mut raster);
+        Ok(raster)
+    }
+
+    /// Read tiles covering the window with corners `ll` and `ur` in TMS CRS
+    async fn read_xy_window(
+        &self,
+        ll: Coordinate,
+        ur: Coordinate,
+        z: u8,
+    ) -> GeorasterResult<Raster> {
+        let matrix = self.tms.matrix(z);
+        let matrix = matrix.as_ref();
+        let (tile_width, tile_height) = (
+            u16::from(matrix.tile_width) as u32,
+            u16::from(matrix.tile_height) as u32,
+        );
+        let (max_col, max_row) = (
+            u64::from(matrix.matrix_width) - 1,
+            u64::from(matrix.matrix_height) - 1,
+        );
+        let ul_tile = self.tms.xy_tile(ll.x.min(ur.x), ll.y.max(ur.y), z);
+        let lr_tile = self.tms.xy_tile(ll.x.max(ur.x), ll.y.min(ur.y), z);
+        let (min_col, min_row) = (
+            ul_tile.x.min(lr_tile.x).min(max_col),
+            ul_tile.y.min(lr_tile.y).min(max_row),
+        );
+        let (max_col, max_row) = (
+            ul_tile.x.max(lr_tile.x).min(max_col),
+            ul_tile.y.max(lr_tile.y).min(max_row),
+        );
+
+        // Pixel grid of all covering tiles
+        let ul_bounds = self.tms.xy_bounds(&Xyz::new(min_col, min_row, z));
+        let pixel_size = [
+            (ul_bounds.right - ul_bounds.left) / tile_width as f64,
+            -(ul_bounds.top - ul_bounds.bottom) / tile_height as f64,
+        ];
+        let mosaic_width = (max_col - min_col + 1) * tile_width as u64;
+        let mosaic_height = (max_row - min_row + 1) * tile_height as u64;
+        let pixel = |x: f64, y: f64| {
+            (
+                ((x - ul_bounds.left) / pixel_size[0]).clamp(0.0, mosaic_width as f64),
+                ((y - ul_bounds.top) / pixel_size[1]).clamp(0.0, mosaic_height as f64),
+            )
+        };
+        // Pixel window covering the bounding box
+        let (x0, y0) = pixel(ll.x.min(ur.x), ll.y.max(ur.y));
+        let (x1, y1) = pixel(ll.x.max(ur.x), ll.y.min(ur.y));
+        let (x0, y0) = (x0.floor() as u64, y0.floor() as u64);
+        let (x1, y1) = (
+            (x1.ceil() as u64).max(x0 + 1).min(mosaic_width),
+            (y1.ceil() as u64).max(y0 + 1).min(mosaic_height),
+        );
+        let (width, height) = (x1.saturating_sub(x0), y1.saturating_sub(y0));
+        let pixels = width.saturating_mul(height);
+        if pixels > self.max_pixels || width > u32::MAX as u64 || height > u32::MAX as u64 {
+            return Err(GeorasterError::WindowTooLarge(pixels, self.max_pixels));
+        }
+        let mut raster = Raster::new(
+            width as u32,
+            height as u32,
+            [
+                ul_bounds.left + x0 as f64 * pixel_size[0],
+                ul_bounds.top + y0 as f64 * pixel_size[1],
+            ],
+            pixel_size,
+        );
+
+        for row in min_row..=max_row {
+            for col in min_col..=max_col {
+                let Some(tile) = self.get_optional_tile(&Xyz::new(col, row, z)).await? else {
+                    continue;
+                };
+                let (width, height) = tile.dimensions();
+                let tile_x0 = (col - min_col) * tile_width as u64;
+                let tile_y0 = (row - min_row) * tile_height as u64;
+                for ty in 0..height.min(tile_height) {
+                    let y = tile_y0 + ty as u64;
+                    if y < y0 || y >= y1 {
+                        continue;
+                    }
+                    for tx in 0..width.min(tile_width) {
+                        let x = tile_x0 + tx as u64;
+                        if x >= x0 && x < x1 {
+                            raster.set((x - x0) as u32, (y - y0) as u32, tile.pixel(tx, ty));
+                        }
+                    }
+                }
+            }
+        }
+        Ok(raster)
+    }
+
+    /// Project WGS84 longitude/latitude into the CRS of the tile matrix set
+    ///
+    /// The result is in x/y (easting/northing) order, independent of the CRS axis order.
+    fn project(&self, coord: Coordinate) -> GeorasterResult<Coordinate> {
+        if is_geographic(&self.tms) {
+            return Ok(coord);
+        }
+        let xy = self.tms.xy(coord.x, coord.y)?;
+        Ok(Coordinate { x: xy.x, y: xy.y })
+    }
+
+    /// Tile containing a geographical location and the location in TMS CRS
+    fn tile_at(&self, coord: Coordinate, z: u8) -> GeorasterResult<(Xyz, Coordinate)> {
+        let xy = self.project(coord)?;
+        Ok((self.tms.xy_tile(xy.x, xy.y, z), xy))
+    }
+
+    /// Raster value of `tile` at location `xy` in TMS CRS
+    fn tile_pixel(&self, tile: &Tile, xyz: &Xyz, xy: Coordinate) -> Option<RasterValue> {
+        let bounds = self.tms.xy_bounds(xyz);
+        let (width, height) = tile.dimensions();
+        let (px, py) = self.coord_to_pixel(&bounds, xy, width, height)?;
+        Some(tile.pixel(px, py))
+    }
+
+    /// Pixel of tile with `bounds` in TMS CRS containing `coord`
     fn coord_to_pixel(
         &self,
-        bounds: &BoundingBox,
+        bounds: &tile_grid::BoundingBox,
         coord: impl Into<Coordinate>,
         w: u32,
         h: u32,
@@ -61,20 +684,223 @@ impl PmtilesRasterReader {
         let pixel_size_x = (bounds.right - bounds.left).abs() / w as f64;
         let pixel_size_y = (bounds.top - bounds.bottom).abs() / h as f64;
         let coord = coord.into();
-        Some((
-            ((coord.x - origin_x) / pixel_size_x).round() as u32,
-            // ((coord.y - origin_y) / pixel_size_y).round() as u32,
-            ((origin_y - coord.y) / pixel_size_y).round() as u32,
-        ))
+        let px = ((coord.x - origin_x) / pixel_size_x).floor();
+        let py = ((origin_y - coord.y) / pixel_size_y).floor();
+        // Coordinates on the right or bottom tile edge belong to the last pixel
+        if px < 0.0 || py < 0.0 || px > w as f64 || py > h as f64 {
+            return None;
+        }
+        Some(((px as u32).min(w - 1), (py as u32).min(h - 1)))
+    }
+}
+
+impl<B: AsyncBackend + Send + Sync> AsyncGeoRaster for PmtilesRasterReader<B> {
+    /// Archive bounds in TMS CRS
+    fn extent(&self) -> Option<BoundingBox> {
+        let bounds = self.bounds();
+        let ll = self.project((bounds.left, bounds.bottom).into()).ok()?;
+        let ur = self.project((bounds.right, bounds.top).into()).ok()?;
+        Some(BoundingBox {
+            epsg: AsyncGeoRaster::epsg_code(self),
+            ..BoundingBox::new(ll.x, ll.y, ur.x, ur.y)
+        })
+    }
+
+    fn epsg_code(&self) -> Option<u32> {
+        match self.tms.crs().as_known_crs().as_str() {
+            "OGC:CRS84" => Some(4326),
+            crs => crs.strip_prefix("EPSG:")?.parse().ok(),
+        }
+    }
+
+    /// Resolution at maximum zoom level
+    fn resolution(&self) -> Option<[f64; 2]> {
+        let z = self.max_zoom();
+        let matrix = self.tms.matrix(z);
+        let matrix = matrix.as_ref();
+        let bounds = self.tms.xy_bounds(&Xyz::new(0, 0, z));
+        Some([
+            (bounds.right - bounds.left).abs() / u16::from(matrix.tile_width) as f64,
+            (bounds.top - bounds.bottom).abs() / u16::from(matrix.tile_height) as f64,
+        ])
+    }
+
+    /// Tile pixels are returned as single raster value
+    fn band_count(&self) -> usize {
+        1
+    }
+
+    /// Value from the most detailed available tile
+    async fn sample(&self, coord: Coordinate) -> GeorasterResult<RasterValue> {
+        match AsyncGeoRaster::extent(self) {
+            Some(extent)
+                if (extent.left..=extent.right).contains(&coord.x)
+                    && (extent.bottom..=extent.top).contains(&coord.y) =>
+            {
+                self.best_pixel_xy(coord).await
+            }
+            _ => Ok(RasterValue::NoData),
+        }
+    }
+
+    /// Read tiles of maximum zoom level
+    async fn read_extent(&self, bbox: &BoundingBox) -> GeorasterResult<Raster> {
+        let ll = Coordinate {
+            x: bbox.left,
+            y: bbox.bottom,
+        };
+        let ur = Coordinate {
+            x: bbox.right,
+            y: bbox.top,
+        };
+        self.read_xy_window(ll, ur, self.max_zoom()).await
+    }
+}
+
+/// Elevation encoding from `encoding` entry in metadata JSON
+fn metadata_encoding(metadata: &serde_json::Value) -> Option<ElevationEncoding> {
+    metadata.get("encoding")?.as_str()?.parse().ok()
+}
+
+/// Tile matrix set from `tile_matrix_set` entry in metadata JSON
+fn metadata_tms(metadata: &serde_json::Value) -> GeorasterResult<Option<Tms>> {
+    let tms = match metadata.get("tile_matrix_set") {
+        Some(serde_json::Value::String(id)) => tms().lookup(id)?,
+        Some(value @ serde_json::Value::Object(_)) => {
+            let definition = TileMatrixSetOps::from_json(&value.to_string())
+                .map_err(|e| GeorasterError::InvalidMetadata(e.to_string()))?;
+            let mut registry = TileMatrixSets::new();
+            registry.register(vec![definition], false)?;
+            let id = registry.list().next().cloned().unwrap_or_default();
+            let mut tms = registry.lookup(&id)?;
+            // EPSG:4326 has lat/lon axis order, which `tile-grid` only
+            // applies to the point of origin with explicit `orderedAxes`
+            if tms.tms.ordered_axes.is_none() && tms.crs().as_known_crs() == "EPSG:4326" {
+                tms.tms.ordered_axes = Some(vec!["Lat".to_string(), "Lon".to_string()]);
+            }
+            tms
+        }
+        _ => return Ok(None),
+    };
+    Ok(Some(tms))
+}
+
+/// TMS with geographic CRS, where coordinates are not projected
+///
+/// Coordinates are always passed in longitude/latitude order, also for
+/// EPSG:4326 with lat/lon axis order like `WorldCRS84Quad`, since `tile-grid`
+/// swaps the point of origin according to the `orderedAxes` of the TMS.
+fn is_geographic(tms: &Tms) -> bool {
+    matches!(tms.crs().as_known_crs().as_str(), "EPSG:4326" | "OGC:CRS84")
+}
+
+/// Decoded tile
+enum Tile {
+    Image(DynamicImage),
+    /// TIFF tile with raster values in row-major order
+    Raster {
+        width: u32,
+        height: u32,
+        values: Vec<RasterValue>,
+    },
+}
+
+impl Tile {
+    /// Decode tile data. TIFF tiles are decoded with `GeoTiffReader`, which
+    /// supports all sample formats and applies GDAL scale/offset and NoData.
+    fn decode(bytes: Bytes) -> GeorasterResult<Self> {
+        if bytes.starts_with(b"II*\0") || bytes.starts_with(b"MM\0*") {
+            let mut tiff = GeoTiffReader::open(Cursor::new(bytes))?;
+            tiff.apply_scale_offset(true);
+            let (width, height) = tiff.image_info().dimensions.unwrap_or((0, 0));
+            let values = tiff.read_window(0, 0, width, height);
+            return Ok(Tile::Raster {
+                width,
+                height,
+                values,
+            });
+        }
+        let img = ImageReader::new(Cursor::new(bytes))
+            .with_guessed_format()?
+            .decode()?;
+        Ok(Tile::Image(img))
+    }
+
+    fn dimensions(&self) -> (u32, u32) {
+        match self {
+            Tile::Image(img) => img.dimensions(),
+            Tile::Raster { width, height, .. } => (*width, *height),
+        }
+    }
+
+    fn pixel(&self, x: u32, y: u32) -> RasterValue {
+        match self {
+            Tile::Image(img) => pixel_value(img, x, y),
+            Tile::Raster { width, values, .. } => values
+                .get(y as usize * *width as usize + x as usize)
+                .copied()
+                .unwrap_or(RasterValue::NoData),
+        }
+    }
+}
+
+/// Raster value of image pixel
+///
+/// Pixels with alpha channel value 0 are returned as `RasterValue::NoData`.
+fn pixel_value(img: &DynamicImage, x: u32, y: u32) -> RasterValue {
+    match img {
+        DynamicImage::ImageLuma8(img) => RasterValue::U8(img.get_pixel(x, y).0[0]),
+        DynamicImage::ImageLumaA8(img) => match img.get_pixel(x, y).0 {
+            [_, 0] => RasterValue::NoData,
+            [v, _] => RasterValue::U8(v),
+        },
+        DynamicImage::ImageRgb8(img) => (*img.get_pixel(x, y)).into(),
+        DynamicImage::ImageRgba8(img) => (*img.get_pixel(x, y)).into(),
+        DynamicImage::ImageLuma16(img) => RasterValue::U16(img.get_pixel(x, y).0[0]),
+        DynamicImage::ImageLumaA16(img) => match img.get_pixel(x, y).0 {
+            [_, 0] => RasterValue::NoData,
+            [v, _] => RasterValue::U16(v),
+        },
+        DynamicImage::ImageRgb16(img) => {
+            let [r, g, b] = img.get_pixel(x, y).0;
+            RasterValue::Rgb16(r, g, b)
+        }
+        DynamicImage::ImageRgba16(img) => match img.get_pixel(x, y).0 {
+            [_, _, _, 0] => RasterValue::NoData,
+            [r, g, b, a] => RasterValue::Rgba16(r, g, b, a),
+        },
+        DynamicImage::ImageRgb32F(img) => {
+            let [r, g, b] = img.get_pixel(x, y).0;
+            RasterValue::Rgb32F(r, g, b)
+        }
+        DynamicImage::ImageRgba32F(img) => match img.get_pixel(x, y).0 {
+            [_, _, _, 0.0] => RasterValue::NoData,
+            [r, g, b, a] => RasterValue::Rgba32F(r, g, b, a),
+        },
+        img => img.get_pixel(x, y).into(),
+    }
+}
+
+/// In-memory PMTiles backend
+pub struct MemoryBackend(Bytes);
+
+impl AsyncBackend for MemoryBackend {
+    async fn read(&self, offset: usize, length: usize) -> PmtResult<Bytes> {
+        let start = offset.min(self.0.len());
+        let end = offset.saturating_add(length).min(self.0.len());
+        Ok(self.0.slice(start..end))
     }
 }
 
 impl<T: Pixel<Subpixel = u8>> From<T> for RasterValue {
     fn from(pixel: T) -> Self {
         match (T::CHANNEL_COUNT, pixel.to_rgba().0) {
+            (1, [v, _, _, _]) => RasterValue::U8(v),
+            (2, [_, _, _, 0]) => RasterValue::NoData,
+            (2, [v, _, _, _]) => RasterValue::U8(v),
             (3, [r, g, b, _a]) => RasterValue::Rgb8(r, g, b),
-            (4, [r, g, b, a]) => RasterValue::Rgba8(r, g, b, a),
-            _ => unimplemented!(), // TODO: Howto impl for u16, etc.?
+            (_, [_, _, _, 0]) => RasterValue::NoData,
+            (_, [r, g, b, a]) => RasterValue::Rgba8(r, g, b, a),
         }
     }
 }
@@ -82,6 +908,14 @@ impl<T: Pixel<Subpixel = u8>> From<T> for RasterValue {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::geo::WEB_MERCATOR_RADIUS;
+    use crate::pmtiles_writer::PmtilesRasterWriter;
+    use crate::ElevationEncoding;
+    use image::ImageBuffer;
+    use std::fs::File;
+    use std::io::BufReader;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::sync::{Arc, OnceLock};
 
     async fn test_tiles() -> PmtilesRasterReader {
         PmtilesRasterReader::open("data/ch-nw.pmtiles")
@@ -128,7 +962,731 @@ mod tests {
                 .await
                 .unwrap()
                 .height(),
-            1598.5294117647063
+            1598.52734375
+        );
+    }
+
+    /// Terrarium encoded tiles of `scaled_int16.tif`, written once for all tests
+    fn test_archive() -> Bytes {
+        static ARCHIVE: OnceLock<Bytes> = OnceLock::new();
+        ARCHIVE
+            .get_or_init(|| {
+                let img_file = BufReader::new(
+                    File::open("data/tiff/scaled_int16.tif").expect("Open image file"),
+                );
+                let mut tiff = GeoTiffReader::open(img_file).expect("Open Tiff");
+                tiff.apply_scale_offset(true);
+                let mut data = Cursor::new(Vec::new());
+                PmtilesRasterWriter::new(4, 8)
+                    .elevation_encoding(ElevationEncoding::Terrarium)
+                    .write(&mut tiff, &mut data)
+                    .unwrap();
+                data.into_inner().into()
+            })
+            .clone()
+    }
+
+    #[tokio::test]
+    async fn memory_backend() {
+        let pmtiles = PmtilesRasterReader::from_bytes(test_archive())
+            .await
+            .unwrap();
+        let height = pmtiles
+            .get_pixel_at(8, (8.25, 46.75))
+            .await
+            .unwrap()
+            .height();
+        assert!((height - 2.0).abs() < 0.01);
+    }
+
+    /// Serve `data` on a local port with HTTP range requests, returns its URL
+    #[cfg(feature = "pmtiles-http")]
+    async fn serve_ranges(data: Bytes) -> String {
+        use tokio::io::{AsyncReadExt, AsyncWriteExt};
+
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        tokio::spawn(async move {
+            while let Ok((mut stream, _)) = listener.accept().await {
+                let data = data.clone();
+                tokio::spawn(async move {
+                    let mut request = Vec::new();
+                    let mut buf = [0; 1024];
+                    while !request.windows(4).any(|w| w == b"\r\n\r\n") {
+                        match stream.read(&mut buf).await {
+                            Ok(0) | Err(_) => return,
+                            Ok(n) => request.extend_from_slice(&buf[..n]),
+                        }
+                    }
+                    let request = String::from_utf8_lossy(&request).to_ascii_lowercase();
+                    let range = request
+                        .lines()
+                        .find_map(|line| line.strip_prefix("range: bytes="))
+                        .and_then(|range| range.split_once('-'))
+                        .and_then(|(start, end)| Some((start.parse().ok()?, end.parse().ok()?)));
+                    let response = match range {
+                        Some((start, end)) if start < data.len() => {
+                            let end = usize::min(end, data.len() - 1);
+                            let header = format!(
+                                "HTTP/1.1 206 Partial Content\r\n\
+                                 Content-Range: bytes {start}-{end}/{}\r\n\
+                                 Content-Length: {}\r\nConnection: close\r\n\r\n",
+                                data.len(),
+                                end + 1 - start
+                            );
+                            [header.as_bytes(), &data[start..=end]].concat()
+                        }
+                        _ => b"HTTP/1.1 416 Range Not Satisfiable\r\n\
+                               Content-Length: 0\r\nConnection: close\r\n\r\n"
+                            .to_vec(),
+                    };
+                    stream.write_all(&response).await.ok();
+                    stream.shutdown().await.ok();
+                });
+            }
+        });
+        format!("http://{addr}/scaled_int16.pmtiles")
+    }
+
+    #[cfg(feature = "pmtiles-http")]
+    #[tokio::test]
+    async fn http_backend() {
+        let url = serve_ranges(test_archive()).await;
+        let pmtiles = PmtilesRasterReader::open_url(&url).await.unwrap();
+        assert_eq!((pmtiles.min_zoom(), pmtiles.max_zoom()), (4, 8));
+        let height = pmtiles.get_height_at(8, (7.25, 46.25)).await.unwrap();
+        assert!((height - 5.0).abs() < 0.01);
+        let heights = pmtiles
+            .get_pixels_at(8, &[(8.25, 46.75).into(), (7.25, 46.25).into()])
+            .await
+            .unwrap();
+        assert!((heights[0].height() - 2.0).abs() < 0.01);
+
+        // Server without archive
+        let url = serve_ranges(Bytes::new()).await;
+        assert!(PmtilesRasterReader::open_url(&url).await.is_err());
+    }
+
+    /// Backend counting range requests
+    struct CountingBackend {
+        data: Bytes,
+        requests: Arc<AtomicUsize>,
+    }
+
+    impl AsyncBackend for CountingBackend {
+        async fn read(&self, offset: usize, length: usize) -> PmtResult<Bytes> {
+            self.requests.fetch_add(1, Ordering::Relaxed);
+            let end = (offset + length).min(self.data.len());
+            Ok(self.data.slice(offset.min(end)..end))
+        }
+    }
+
+    /// Reader of `test_archive` with its number of backend requests
+    async fn counting_reader() -> (PmtilesRasterReader<CountingBackend>, Arc<AtomicUsize>) {
+        let requests = Arc::new(AtomicUsize::new(0));
+        let backend = CountingBackend {
+            data: test_archive(),
+            requests: requests.clone(),
+        };
+        let pmtiles = PmtilesRasterReader::from_backend(backend).await.unwrap();
+        (pmtiles, requests)
+    }
+
+    #[tokio::test]
+    async fn custom_backend() {
+        let (pmtiles, requests) = counting_reader().await;
+        let height = pmtiles
+            .get_pixel_at(8, (7.25, 46.25))
+            .await
+            .unwrap()
+            .height();
+        assert!((height - 5.0).abs() < 0.01);
+        // Header with root directory, metadata and tile data
+        assert_eq!(requests.load(Ordering::Relaxed), 3);
+    }
+
+    #[tokio::test]
+    async fn tile_cache() {
+        let (pmtiles, requests) = counting_reader().await;
+        requests.store(0, Ordering::Relaxed);
+        for coord in [(7.25, 46.25), (7.75, 46.25), (7.3, 46.3)] {
+            pmtiles.get_pixel_at(8, coord).await.unwrap();
+        }
+        assert_eq!(requests.load(Ordering::Relaxed), 1);
+
+        let pmtiles = pmtiles.with_cache_size(0);
+        requests.store(0, Ordering::Relaxed);
+        for coord in [(7.25, 46.25), (7.3, 46.3)] {
+            pmtiles.get_pixel_at(8, coord).await.unwrap();
+        }
+        assert_eq!(requests.load(Ordering::Relaxed), 2);
+    }
+
+    #[tokio::test]
+    async fn batch_pixels() {
+        let (pmtiles, requests) = counting_reader().await;
+        let pmtiles = pmtiles.with_cache_size(0);
+        requests.store(0, Ordering::Relaxed);
+        let coords: Vec<Coordinate> = [(8.25, 46.75), (7.25, 46.25), (8.3, 46.7), (12.0, 46.5)]
+            .into_iter()
+            .map(Coordinate::from)
+            .collect();
+        let values = pmtiles.get_pixels_at(8, &coords).await.unwrap();
+        let heights: Vec<_> = values.iter().map(|v| v.height().round()).collect();
+        assert_eq!(heights[..3], [2.0, 5.0, 2.0]);
+        // Missing tile
+        assert_eq!(values[3], RasterValue::NoData);
+        // One tile for three points, the missing tile is not in the root directory
+        assert_eq!(requests.load(Ordering::Relaxed), 1);
+        // Same values as single pixel queries
+        for (coord, value) in coords.iter().zip(&values) {
+            assert_eq!(pmtiles.get_pixel_at(8, *coord).await.unwrap(), *value);
+        }
+    }
+
+    #[tokio::test]
+    async fn elevation_encoding() {
+        let metadata = serde_json::json!({"encoding": "mapbox"});
+        assert_eq!(
+            metadata_encoding(&metadata),
+            Some(ElevationEncoding::Mapbox)
+        );
+        let metadata = serde_json::json!({"name": "dem"});
+        assert_eq!(metadata_encoding(&metadata), None);
+
+        let pmtiles = PmtilesRasterReader::from_bytes(test_archive())
+            .await
+            .unwrap();
+        assert_eq!(pmtiles.encoding(), ElevationEncoding::Terrarium);
+        let height = pmtiles.get_height_at(8, (7.25, 46.25)).await.unwrap();
+        assert!((height - 5.0).abs() < 0.01);
+        // NoData pixel
+        let height = pmtiles.get_height_at(8, (7.75, 46.25)).await.unwrap();
+        assert!(height.is_nan());
+
+        let pmtiles = pmtiles.with_encoding(ElevationEncoding::Mapbox);
+        let height = pmtiles.get_height_at(8, (7.25, 46.25)).await.unwrap();
+        assert!((height - 5.0).abs() > 1.0);
+    }
+
+    /// Terrarium encoded WorldCRS84Quad tiles of the western hemisphere at zoom
+    /// level 1 with height `1000 * (tile column + 2 * tile row) + pixel column`
+    fn wgs84_archive(metadata: &str) -> Vec<u8> {
+        let mut data = Cursor::new(Vec::new());
+        let mut writer = pmt::PmTilesWriter::new(pmt::TileType::Png)
+            .min_zoom(1)
+            .max_zoom(1)
+            .metadata(metadata)
+            .create(&mut data)
+            .unwrap();
+        for (x, y) in [(0, 0), (1, 0), (0, 1), (1, 1)] {
+            let img = image::RgbImage::from_fn(256, 256, |px, _| {
+                let height = (1000 * (x + 2 * y) + px) as f64;
+                image::Rgb(ElevationEncoding::Terrarium.encode_rgb(height).unwrap())
+            });
+            let mut tile = Vec::new();
+            img.write_to(&mut Cursor::new(&mut tile), image::ImageFormat::Png)
+                .unwrap();
+            writer
+                .add_tile(TileCoord::new(1, x, y).unwrap(), &tile)
+                .unwrap();
+        }
+        writer.finalize().unwrap();
+        data.into_inner()
+    }
+
+    #[tokio::test]
+    async fn tile_matrix_set() {
+        let archive = wgs84_archive(r#"{"tile_matrix_set":"WorldCRS84Quad"}"#);
+        let pmtiles = PmtilesRasterReader::from_bytes(archive).await.unwrap();
+        assert_eq!(pmtiles.tms().tms.id, "WorldCRS84Quad");
+        let heights = pmtiles
+            .get_pixels_at(1, &[(-170.0, 10.0).into(), (-60.0, -30.0).into()])
+            .await
+            .unwrap();
+        assert_eq!(heights[0].height(), 28.0);
+        assert_eq!(heights[1].height(), 3085.0);
+
+        let pmtiles = PmtilesRasterReader::from_bytes(wgs84_archive("{}"))
+            .await
+            .unwrap();
+        assert_eq!(pmtiles.tms().tms.id, "WebMercatorQuad");
+        let pmtiles = pmtiles.with_tms_id("WorldCRS84Quad").unwrap();
+        let height = pmtiles.get_pixel_at(1, (-60.0, -30.0)).await.unwrap();
+        assert_eq!(height.height(), 3085.0);
+
+        // WorldCRS84Quad has EPSG:4326 with lat/lon axis order, input is lon/lat
+        let location = Coordinate { x: -60.0, y: -30.0 };
+        let tile = pmtiles.tms().xy_tile(location.x, location.y, 1);
+        assert_eq!((tile.x, tile.y), (1, 1));
+        let bounds = pmtiles.tms().xy_bounds(&tile);
+        assert!((bounds.left + 90.0).abs() < 1e-9 && bounds.top.abs() < 1e-9);
+
+        // Custom EPSG:4326 definition without explicit axis order
+        let mut definition =
+            serde_json::to_value(&tms().lookup("WorldCRS84Quad").unwrap().tms).unwrap();
+        definition.as_object_mut().unwrap().remove("orderedAxes");
+        definition["id"] = "CustomQuad".into();
+        let metadata = serde_json::json!({ "tile_matrix_set": definition }).to_string();
+        let pmtiles = PmtilesRasterReader::from_bytes(wgs84_archive(&metadata))
+            .await
+            .unwrap();
+        assert_eq!(pmtiles.tms().tms.id, "CustomQuad");
+        let height = pmtiles.get_pixel_at(1, location).await.unwrap();
+        assert_eq!(height.height(), 3085.0);
+
+        let archive = wgs84_archive(r#"{"tile_matrix_set":"UnknownQuad"}"#);
+        assert_eq!(
+            PmtilesRasterReader::from_bytes(archive)
+                .await
+                .err()
+                .unwrap()
+                .to_string(),
+            "TMS registry error - Tile Matrix set not found: `UnknownQuad`"
+        );
+    }
+
+    /// Global WebMercatorQuad pixel position of WGS84 location
+    fn reference_pixel(lon: f64, lat: f64, z: u8) -> (f64, f64) {
+        let n = 256.0 * 2f64.powi(z as i32);
+        let lat = lat.to_radians();
+        let x = (lon + 180.0) / 360.0 * n;
+        let y = (1.0 - (lat.tan() + 1.0 / lat.cos()).ln() / std::f64::consts::PI) / 2.0 * n;
+        (x, y)
+    }
+
+    #[tokio::test]
+    async fn wgs84_input() {
+        // Tile with pixel column and row as red and green value
+        // Chasseral 47.133037, 7.059309
+        let z = 16;
+        let (gx, gy) = reference_pixel(7.059309, 47.133037, z);
+        let (tx, ty) = ((gx / 256.0) as u32, (gy / 256.0) as u32);
+        let mut data = Cursor::new(Vec::new());
+        let mut writer = pmt::PmTilesWriter::new(pmt::TileType::Png)
+            .min_zoom(z)
+            .max_zoom(z)
+            .create(&mut data)
+            .unwrap();
+        let img = image::RgbImage::from_fn(256, 256, |px, py| image::Rgb([px as u8, py as u8, 0]));
+        let mut tile = Vec::new();
+        img.write_to(&mut Cursor::new(&mut tile), image::ImageFormat::Png)
+            .unwrap();
+        writer
+            .add_tile(TileCoord::new(z, tx, ty).unwrap(), &tile)
+            .unwrap();
+        writer.finalize().unwrap();
+        let pmtiles = PmtilesRasterReader::from_bytes(data.into_inner())
+            .await
+            .unwrap();
+
+        for (dlon, dlat) in [(0.0, 0.0), (0.001, 0.0), (0.0, -0.001), (0.0015, -0.0025)] {
+            let (lon, lat) = (7.059309 + dlon, 47.133037 + dlat);
+            let (gx, gy) = reference_pixel(lon, lat, z);
+            let (ex, ey) = (gx - tx as f64 * 256.0, gy - ty as f64 * 256.0);
+            let RasterValue::Rgb8(px, py, _) = pmtiles.get_pixel_at(z, (lon, lat)).await.unwrap()
+            else {
+                panic!("Rgb8 value expected");
+            };
+            assert!((px as f64 - ex).abs() <= 1.0, "{px} <> {ex}");
+            assert!((py as f64 - ey).abs() <= 1.0, "{py} <> {ey}");
+
+            // Same pixel with WebMercator input
+            let xy = (
+                lon.to_radians() * WEB_MERCATOR_RADIUS,
+                (std::f64::consts::FRAC_PI_4 + lat.to_radians() / 2.0)
+                    .tan()
+                    .ln()
+                    * WEB_MERCATOR_RADIUS,
+            );
+            assert_eq!(
+                pmtiles.get_pixel_at_xy(z, xy).await.unwrap(),
+                RasterValue::Rgb8(px, py, 0)
+            );
+        }
+    }
+
+    #[test]
+    fn image_pixel_values() {
+        let img = DynamicImage::ImageLuma8(image::GrayImage::from_pixel(1, 1, image::Luma([7])));
+        assert_eq!(pixel_value(&img, 0, 0), RasterValue::U8(7));
+        let img = DynamicImage::ImageLumaA8(image::GrayAlphaImage::from_pixel(
+            1,
+            1,
+            image::LumaA([7, 0]),
+        ));
+        assert_eq!(pixel_value(&img, 0, 0), RasterValue::NoData);
+        let img = DynamicImage::ImageLuma16(ImageBuffer::from_pixel(1, 1, image::Luma([1607u16])));
+        assert_eq!(pixel_value(&img, 0, 0), RasterValue::U16(1607));
+        let img = DynamicImage::ImageRgb16(ImageBuffer::from_pixel(1, 1, image::Rgb([1u16, 2, 3])));
+        assert_eq!(pixel_value(&img, 0, 0), RasterValue::Rgb16(1, 2, 3));
+        let img =
+            DynamicImage::ImageRgba16(ImageBuffer::from_pixel(1, 1, image::Rgba([1u16, 2, 3, 0])));
+        assert_eq!(pixel_value(&img, 0, 0), RasterValue::NoData);
+        let img = DynamicImage::ImageRgba8(image::RgbaImage::from_pixel(
+            1,
+            1,
+            image::Rgba([1, 2, 3, 0]),
+        ));
+        assert_eq!(pixel_value(&img, 0, 0), RasterValue::NoData);
+        let img = DynamicImage::ImageRgb32F(ImageBuffer::from_pixel(
+            1,
+            1,
+            image::Rgb([1607.5f32, 0.5, -1.0]),
+        ));
+        assert_eq!(
+            pixel_value(&img, 0, 0),
+            RasterValue::Rgb32F(1607.5, 0.5, -1.0)
+        );
+        let img = DynamicImage::ImageRgba32F(ImageBuffer::from_pixel(
+            1,
+            1,
+            image::Rgba([1607.5f32, 0.5, -1.0, 1.0]),
+        ));
+        assert_eq!(
+            pixel_value(&img, 0, 0),
+            RasterValue::Rgba32F(1607.5, 0.5, -1.0, 1.0)
+        );
+        let img = DynamicImage::ImageRgba32F(ImageBuffer::from_pixel(
+            1,
+            1,
+            image::Rgba([1607.5f32, 0.5, -1.0, 0.0]),
+        ));
+        assert_eq!(pixel_value(&img, 0, 0), RasterValue::NoData);
+        assert_eq!(RasterValue::from(image::Luma([7u8])), RasterValue::U8(7));
+    }
+
+    #[tokio::test]
+    async fn raw_elevation_tiles() {
+        let img_file =
+            BufReader::new(File::open("data/tiff/scaled_int16.tif").expect("Open image file"));
+        let mut tiff = GeoTiffReader::open(img_file).expect("Open Tiff");
+        tiff.apply_scale_offset(true);
+        let mut data = Cursor::new(Vec::new());
+        PmtilesRasterWriter::new(8, 8)
+            .elevation_encoding(ElevationEncoding::Raw)
+            .write(&mut tiff, &mut data)
+            .unwrap();
+        let pmtiles = PmtilesRasterReader::from_bytes(data.into_inner())
+            .await
+            .unwrap();
+        assert_eq!(pmtiles.encoding(), ElevationEncoding::Raw);
+        let value = pmtiles.get_pixel_at(8, (7.25, 46.25)).await.unwrap();
+        assert_eq!(value, RasterValue::U16(5));
+        let height = pmtiles.get_height_at(8, (7.25, 46.25)).await.unwrap();
+        assert_eq!(height, 5.0);
+    }
+
+    #[tokio::test]
+    async fn float_tiff_tiles() {
+        use tiff::encoder::{colortype, TiffEncoder};
+
+        let values: Vec<f32> = (0..256 * 256).map(|i| (i % 256) as f32 + 0.5).collect();
+        let mut tile = Cursor::new(Vec::new());
+        TiffEncoder::new(&mut tile)
+            .unwrap()
+            .write_image::<colortype::Gray32Float>(256, 256, &values)
+            .unwrap();
+        let mut data = Cursor::new(Vec::new());
+        let mut writer = pmt::PmTilesWriter::new(pmt::TileType::Unknown)
+            .min_zoom(0)
+            .max_zoom(0)
+            .create(&mut data)
+            .unwrap();
+        writer
+            .add_tile(TileCoord::new(0, 0, 0).unwrap(), tile.get_ref())
+            .unwrap();
+        writer.finalize().unwrap();
+        let pmtiles = PmtilesRasterReader::from_bytes(data.into_inner())
+            .await
+            .unwrap();
+        assert_eq!(
+            pmtiles.get_pixel_at(0, (0.5, 0.0)).await.unwrap(),
+            RasterValue::F32(128.5)
+        );
+        assert!(pmtiles.get_tile(&Xyz::new(0, 0, 0)).await.is_err());
+    }
+
+    #[tokio::test]
+    async fn best_zoom() {
+        // Sparse archive with full tile at zoom 0 and north-west tile at zoom 1
+        let mut data = Cursor::new(Vec::new());
+        let mut writer = pmt::PmTilesWriter::new(pmt::TileType::Png)
+            .min_zoom(0)
+            .max_zoom(1)
+            .bounds(-180.0, -60.0, 180.0, 60.0)
+            .create(&mut data)
+            .unwrap();
+        let tiles = [
+            (TileCoord::new(0, 0, 0).unwrap(), None),
+            (TileCoord::new(1, 0, 0).unwrap(), Some(200)),
+        ];
+        for (coord, value) in tiles {
+            let img = image::GrayImage::from_fn(256, 256, |px, _| {
+                image::Luma([value.unwrap_or(px as u8)])
+            });
+            let mut tile = Vec::new();
+            img.write_to(&mut Cursor::new(&mut tile), image::ImageFormat::Png)
+                .unwrap();
+            writer.add_tile(coord, &tile).unwrap();
+        }
+        writer.finalize().unwrap();
+        let pmtiles = PmtilesRasterReader::from_bytes(data.into_inner())
+            .await
+            .unwrap();
+
+        let value = pmtiles.get_pixel_at_best((-90.0, 45.0)).await.unwrap();
+        assert_eq!(value, RasterValue::U8(200));
+        // Pixel of parent tile
+        let value = pmtiles.get_pixel_at_best((91.0, 45.0)).await.unwrap();
+        assert_eq!(value, RasterValue::U8(192));
+        let value = pmtiles.get_pixel_at_best((46.0, -45.0)).await.unwrap();
+        assert_eq!(value, RasterValue::U8(160));
+        // Outside of archive bounds
+        let value = pmtiles.get_pixel_at_best((0.0, 70.0)).await.unwrap();
+        assert_eq!(value, RasterValue::NoData);
+
+        let pmtiles = PmtilesRasterReader::from_bytes(test_archive())
+            .await
+            .unwrap();
+        let value = pmtiles.get_pixel_at_best((8.25, 46.75)).await.unwrap();
+        assert!((value.height() - 2.0).abs() < 0.01);
+        let value = pmtiles.get_pixel_at_best((12.0, 46.5)).await.unwrap();
+        assert_eq!(value, RasterValue::NoData);
+    }
+
+    #[tokio::test]
+    async fn read_bbox() {
+        let pmtiles = PmtilesRasterReader::from_bytes(test_archive())
+            .await
+            .unwrap();
+        let bbox = BoundingBox::new(7.2, 46.2, 8.3, 46.8);
+        let raster = pmtiles.read_bbox(&bbox, 8).await.unwrap();
+        let ul = pmtiles.tms().xy(7.2, 46.8).unwrap();
+        let lr = pmtiles.tms().xy(8.3, 46.2).unwrap();
+        assert_eq!(raster.coord_to_pixel((ul.x, ul.y)), Some((0, 0)));
+        assert_eq!(
+            raster.coord_to_pixel((lr.x, lr.y)),
+            Some((raster.width - 1, raster.height - 1))
+        );
+        for (lon, lat) in [(8.25, 46.75), (7.25, 46.25), (8.0, 46.5)] {
+            let xy = pmtiles.tms().xy(lon, lat).unwrap();
+            let (x, y) = raster.coord_to_pixel((xy.x, xy.y)).unwrap();
+            assert_eq!(
+                raster.get(x, y),
+                pmtiles.get_pixel_at(8, (lon, lat)).await.unwrap()
+            );
+        }
+
+        let heights = pmtiles.read_heights(&bbox, 8).await.unwrap();
+        let xy = pmtiles.tms().xy(8.0, 46.5).unwrap();
+        let (x, y) = heights.coord_to_pixel((xy.x, xy.y)).unwrap();
+        assert_eq!(
+            heights.get(x, y),
+            RasterValue::F64(pmtiles.get_height_at(8, (8.0, 46.5)).await.unwrap())
+        );
+
+        // Missing tiles
+        let raster = pmtiles
+            .read_bbox(&BoundingBox::new(11.0, 46.0, 12.0, 47.0), 8)
+            .await
+            .unwrap();
+        assert!(raster.values.iter().all(|v| *v == RasterValue::NoData));
+
+        // Window size limit
+        let world = BoundingBox::new(-180.0, -85.0, 180.0, 85.0);
+        assert!(matches!(
+            pmtiles.read_bbox(&world, 24).await,
+            Err(GeorasterError::WindowTooLarge(..))
+        ));
+        let pmtiles = pmtiles.with_max_pixels(100);
+        assert!(matches!(
+            pmtiles.read_heights(&bbox, 8).await,
+            Err(GeorasterError::WindowTooLarge(..))
+        ));
+    }
+
+    #[tokio::test]
+    async fn terrain() {
+        use crate::terrain::{SlopeUnit, Terrain};
+
+        let pmtiles = PmtilesRasterReader::from_bytes(test_archive())
+            .await
+            .unwrap();
+        let bbox = BoundingBox::new(7.2, 46.2, 8.3, 46.8);
+        let dem = pmtiles.read_heights(&bbox, 8).await.unwrap();
+        let epsg = AsyncGeoRaster::epsg_code(&pmtiles);
+        assert_eq!(epsg, Some(3857));
+        let slope = Terrain::new(&dem)
+            .crs(epsg, is_geographic(pmtiles.tms()))
+            .slope(SlopeUnit::Percent);
+        // WebMercator pixels of 611 m are scaled to about 420 m on the ground
+        let unscaled = Terrain::new(&dem).slope(SlopeUnit::Percent);
+        let mut steps = 0;
+        for y in 0..dem.height {
+            let center_y = dem.origin[1] + (y as f64 + 0.5) * dem.pixel_size[1];
+            let (scale, _) = geo::ground_scale(epsg, center_y);
+            assert!((0.68..0.70).contains(&scale), "{scale}");
+            for x in 0..dem.width {
+                // NoData of the source pixel (1, 1)
+                let Some(value) = slope.get(x, y).as_f64() else {
+                    assert_eq!(dem.get(x, y), RasterValue::NoData);
+                    continue;
+                };
+                let expected = unscaled.get(x, y).as_f64().unwrap() / scale;
+                assert!((value - expected).abs() < 1e-3, "{value} <> {expected}");
+                if value > 0.0 {
+                    steps += 1;
+                }
+            }
+        }
+        // Height steps between the 0.5° source pixels
+        assert!(steps > 0);
+    }
+
+    #[tokio::test]
+    async fn header_metadata() {
+        let pmtiles = PmtilesRasterReader::from_bytes(test_archive())
+            .await
+            .unwrap();
+        assert_eq!((pmtiles.min_zoom(), pmtiles.max_zoom()), (4, 8));
+        assert_eq!(pmtiles.center_zoom(), 4);
+        assert_eq!(pmtiles.tile_type(), TileType::Png);
+        assert_eq!(pmtiles.tile_compression(), Compression::None);
+        assert_eq!(pmtiles.metadata()["encoding"], "terrarium");
+        let bounds = pmtiles.bounds();
+        assert!(bounds.left < 7.25 && bounds.right > 8.25);
+        assert!(bounds.bottom < 46.25 && bounds.top > 46.75);
+        assert_eq!(bounds.epsg, Some(4326));
+        let center = pmtiles.center();
+        assert!((bounds.left..bounds.right).contains(&center.x));
+        assert!((bounds.bottom..bounds.top).contains(&center.y));
+    }
+
+    #[tokio::test]
+    async fn async_geo_raster() {
+        let pmtiles = PmtilesRasterReader::from_bytes(test_archive())
+            .await
+            .unwrap();
+        assert_eq!(AsyncGeoRaster::epsg_code(&pmtiles), Some(3857));
+        assert_eq!(pmtiles.band_count(), 1);
+        let [res_x, res_y] = pmtiles.resolution().unwrap();
+        assert!((res_x - 611.496).abs() < 0.001 && (res_y - 611.496).abs() < 0.001);
+
+        let xy = pmtiles.tms().xy(8.25, 46.75).unwrap();
+        let xy = Coordinate { x: xy.x, y: xy.y };
+        let extent = AsyncGeoRaster::extent(&pmtiles).unwrap();
+        assert_eq!(extent.epsg, Some(3857));
+        assert!(extent.left < xy.x && xy.x < extent.right);
+        assert!(extent.bottom < xy.y && xy.y < extent.top);
+        assert_eq!(
+            pmtiles.sample(xy).await.unwrap(),
+            pmtiles.get_pixel_at(8, (8.25, 46.75)).await.unwrap()
+        );
+        assert_eq!(
+            pmtiles.sample(Coordinate { x: 0.0, y: 0.0 }).await.unwrap(),
+            RasterValue::NoData
         );
+
+        let ll = pmtiles.tms().xy(7.2, 46.2).unwrap();
+        let ur = pmtiles.tms().xy(8.3, 46.8).unwrap();
+        let raster = pmtiles
+            .read_extent(&BoundingBox::new(ll.x, ll.y, ur.x, ur.y))
+            .await
+            .unwrap();
+        let expected = pmtiles
+            .read_bbox(&BoundingBox::new(7.2, 46.2, 8.3, 46.8), 8)
+            .await
+            .unwrap();
+        assert_eq!(raster, expected);
+    }
+
+    #[tokio::test]
+    async fn profile() {
+        let pmtiles = PmtilesRasterReader::from_bytes(test_archive())
+            .await
+            .unwrap();
+        let line = [
+            Coordinate { x: 7.3, y: 46.7 },
+            Coordinate { x: 8.2, y: 46.7 },
+            Coordinate { x: 8.7, y: 45.3 },
+        ];
+        let profile = pmtiles
+            .profile(&line, ProfileStep::Distance(1000.0))
+            .await
+            .unwrap();
+        let first = profile.points[0];
+        let last = profile.points[profile.points.len() - 1];
+        assert_eq!((first.distance, first.coord), (0.0, line[0]));
+        assert_eq!(last.coord, line[2]);
+        // 68.6 km along 46.7° N and 160.4 km to the south-south-east
+        assert!((last.distance - 229_000.0).abs() < 500.0);
+        assert!(profile.points.iter().all(|p| p.value.is_some()));
+        let (first, last) = (first.value.unwrap(), last.value.unwrap());
+        assert!((profile.ascent() - profile.descent() - (last - first)).abs() < 1e-6);
+        let height = pmtiles.get_height_at(8, line[0]).await.unwrap();
+        assert!((first - height).abs() < 5.0, "{first} <> {height}");
+
+        // 0.9° longitude are 164 WebMercator pixels of 611 m at zoom level 8
+        let profile = pmtiles
+            .profile(&line[..2], ProfileStep::PixelCrossing)
+            .await
+            .unwrap();
+        assert!((164..=166).contains(&profile.points.len()));
+        // Ground pixel size at 46.7° N
+        let start = pmtiles.project(line[0]).unwrap();
+        let (scale, _) = geo::ground_scale(AsyncGeoRaster::epsg_code(&pmtiles), start.y);
+        let pixel_size = AsyncGeoRaster::resolution(&pmtiles).unwrap()[0] * scale;
+        let pixels = profile.length() / pixel_size;
+        assert!(
+            (pixels - profile.points.len() as f64).abs() < 3.0,
+            "{pixels}"
+        );
+
+        // Profile read in windows of at most 20 x 20 pixels
+        let windowed = pmtiles
+            .with_max_pixels(400)
+            .profile(&line[..2], ProfileStep::PixelCrossing)
+            .await
+            .unwrap();
+        assert_eq!(windowed.points.len(), profile.points.len());
+        for (a, b) in windowed.points.iter().zip(&profile.points) {
+            assert_eq!((a.distance, a.coord), (b.distance, b.coord));
+            assert!((a.value.unwrap() - b.value.unwrap()).abs() < 1e-9);
+        }
+    }
+
+    #[tokio::test]
+    async fn visibility() {
+        let pmtiles = PmtilesRasterReader::from_bytes(test_archive())
+            .await
+            .unwrap();
+        let observer = Observer {
+            height: 10.0,
+            ..Default::default()
+        };
+        let (from, to) = (
+            Coordinate { x: 7.3, y: 46.7 },
+            Coordinate { x: 7.35, y: 46.7 },
+        );
+        let los = pmtiles
+            .line_of_sight(from, to, &observer)
+            .await
+            .unwrap()
+            .unwrap();
+        // 0.05° longitude at 46.7° N
+        assert!((los.distance - 3_813.0).abs() < 5.0, "{}", los.distance);
+        assert_eq!(los.visible, los.obstacle_distance.is_none());
+
+        let raster = pmtiles.viewshed(from, 5_000.0, &observer).await.unwrap();
+        let (x, y) = raster
+            .coord_to_pixel(pmtiles.project(from).unwrap())
+            .unwrap();
+        assert_eq!(raster.get(x, y), RasterValue::U8(1));
+        let visible = raster.values.iter().filter(|v| **v == RasterValue::U8(1));
+        let hidden = raster.values.iter().filter(|v| **v == RasterValue::U8(0));
+        // Pixels of 611 m WebMercator, 419 m on the ground, within 5 km
+        let count = visible.count() + hidden.count();
+        assert!((400..=500).contains(&count), "{count}");
     }
 }
@@ -0,0 +1,488 @@
+//! PMTiles raster tile pyramid generator.
+
+use crate::crs::Transformer;
+use crate::geotiff::GeoTiffReader;
+use crate::{
+    BoundingBox, ElevationEncoding, GeorasterError, GeorasterResult, RasterValue, SampleType,
+};
+use image::{DynamicImage, ImageBuffer, ImageFormat, Luma, Rgba, RgbaImage};
+use pmt::{PmTilesWriter, TileCoord, TileId, TileType};
+use std::io::{Cursor, Read, Seek, Write};
+use tile_grid::{tms, Xyz};
+
+const MAX_LATITUDE: f64 = 85.0511287798066;
+/// Spacing of exactly transformed tile pixels
+const APPROX_STEP: u32 = 16;
+
+/// Image format of raster tiles
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum TileFormat {
+    Png,
+    /// Lossless WebP (8 bit only)
+    Webp,
+}
+
+/// PMTiles raster writer
+///
+/// Generates a WebMercatorQuad tile pyramid from a GeoTIFF in any CRS supported
+/// by `crs::Transformer`.
+///
+/// ```rust,no_run
+/// use georaster::{geotiff::GeoTiffReader, pmtiles_writer::PmtilesRasterWriter, ElevationEncoding};
+/// use std::{fs::File, io::BufReader};
+///
+/// let img_file = BufReader::new(File::open("dem.tif").unwrap());
+/// let mut tiff = GeoTiffReader::open(img_file).unwrap();
+/// PmtilesRasterWriter::new(0, 12)
+///     .tile_size(512)
+///     .elevation_encoding(ElevationEncoding::Terrarium)
+///     .write(&mut tiff, File::create("dem.pmtiles").unwrap())
+///     .unwrap();
+/// ```
+pub struct PmtilesRasterWriter {
+    tms: tile_grid::Tms,
+    min_zoom: u8,
+    max_zoom: u8,
+    tile_size: u32,
+    format: TileFormat,
+    encoding: Option<ElevationEncoding>,
+}
+
+impl PmtilesRasterWriter {
+    /// Writer for PNG tiles with 256 pixels in the zoom level range `min_zoom..=max_zoom`
+    pub fn new(min_zoom: u8, max_zoom: u8) -> Self {
+        PmtilesRasterWriter {
+            tms: tms().lookup("WebMercatorQuad").unwrap(),
+            min_zoom,
+            max_zoom,
+            tile_size: 256,
+            format: TileFormat::Png,
+            encoding: None,
+        }
+    }
+
+    /// Width and height of tile images in pixels
+    pub fn tile_size(mut self, tile_size: u32) -> Self {
+        self.tile_size = tile_size;
+        self
+    }
+
+    pub fn format(mut self, format: TileFormat) -> Self {
+        self.format = format;
+        self
+    }
+
+    /// Encode raster values as elevations
+    ///
+    /// `Terrarium` and `Mapbox` produce RGB tiles, `Raw` produces 16 bit grayscale tiles.
+    /// Without elevation encoding, raster values are written as 8 bit RGBA images.
+    pub fn elevation_encoding(mut self, encoding: ElevationEncoding) -> Self {
+        self.encoding = Some(encoding);
+        self
+    }
+
+    /// Write tiles of the selected band into a PMTiles archive
+    ///
+    /// Tiles are resampled with nearest neighbour from the best matching overview.
+    /// NoData values are written as transparent pixels and empty tiles are skipped.
+    pub fn write<R: Read + Seek + Send, W: Write + Seek>(
+        &self,
+        tiff: &mut GeoTiffReader<R>,
+        out: W,
+    ) -> GeorasterResult<()> {
+        let epsg = tiff
+            .epsg_code()
+            .ok_or_else(|| GeorasterError::UnsupportedCrs("unknown".to_string()))?;
+        let origin = tiff.origin().ok_or(GeorasterError::NoGeoreference)?;
+        let pixel_size = tiff.pixel_size().ok_or(GeorasterError::NoGeoreference)?;
+        let (width, height) = tiff
+            .images()
+            .first()
+            .and_then(|img| img.dimensions)
+            .ok_or(GeorasterError::NoGeoreference)?;
+        let source = SourceGrid {
+            to_source: Transformer::new(3857, epsg)?,
+            origin,
+            pixel_size,
+            width,
+            height,
+        };
+
+        let lonlat = source.extent().with_epsg(epsg).to_crs(4326)?;
+        let (west, south) = (lonlat.left.max(-180.0), lonlat.bottom.max(-MAX_LATITUDE));
+        let (east, north) = (lonlat.right.min(180.0), lonlat.top.min(MAX_LATITUDE));
+        let ll = self.tms.xy(west, south)?;
+        let ur = self.tms.xy(east, north)?;
+        let xy_extent = tile_grid::BoundingBox::new(ll.x, ll.y, ur.x, ur.y);
+
+        let metadata = match self.encoding {
+            Some(encoding) => format!(r#"{{"encoding":"{encoding}"}}"#),
+            None => "{}".to_string(),
+        };
+        let tile_type = match self.format {
+            TileFormat::Png => TileType::Png,
+            TileFormat::Webp => TileType::Webp,
+        };
+        let mut writer = PmTilesWriter::new(tile_type)
+            .min_zoom(self.min_zoom)
+            .max_zoom(self.max_zoom)
+            .bounds(west as f32, south as f32, east as f32, north as f32)
+            .center(((west + east) / 2.0) as f32, ((south + north) / 2.0) as f32)
+            .center_zoom(self.min_zoom)
+            .metadata(&metadata)
+            .create(out)?;
+
+        // Tiles should be added in the order of their tile IDs
+        let mut tiles: Vec<(TileCoord, Xyz)> = self
+            .tms
+            .xyz_iterator(&xy_extent, self.min_zoom, self.max_zoom)
+            .filter_map(|xyz| Some((TileCoord::new(xyz.z, xyz.x as u32, xyz.y as u32)?, xyz)))
+            .collect();
+        tiles.sort_by_key(|(coord, _)| TileId::from(*coord).value());
+
+        let image_idx = tiff.image_index();
+        for (coord, xyz) in tiles {
+            if let Some(data) = self.render_tile(tiff, &source, &xyz)? {
+                writer.add_tile(coord, &data)?;
+            }
+        }
+        tiff.seek_to_image(image_idx)?;
+        writer.finalize()?;
+        Ok(())
+    }
+
+    /// Encoded tile image or `None` for empty tiles
+    fn render_tile<R: Read + Seek + Send>(
+        &self,
+        tiff: &mut GeoTiffReader<R>,
+        source: &SourceGrid,
+        xyz: &Xyz,
+    ) -> GeorasterResult<Option<Vec<u8>>> {
+        let size = self.tile_size;
+        let positions = source.tile_positions(&self.tms.xy_bounds(xyz), size);
+
+        // Source window of tile in full resolution pixels
+        let (mut min_x, mut min_y) = (f64::INFINITY, f64::INFINITY);
+        let (mut max_x, mut max_y) = (f64::NEG_INFINITY, f64::NEG_INFINITY);
+        for (x, y) in positions.iter().flatten() {
+            min_x = min_x.min(*x);
+            min_y = min_y.min(*y);
+            max_x = max_x.max(*x);
+            max_y = max_y.max(*y);
+        }
+        if max_x < 0.0
+            || max_y < 0.0
+            || min_x >= source.width as f64
+            || min_y >= source.height as f64
+        {
+            return Ok(None);
+        }
+
+        // Use overview with a resolution close to the tile resolution
+        let factor = (max_x - min_x) / size as f64;
+        let (image_idx, (image_width, image_height)) = tiff.best_image(factor);
+        tiff.seek_to_image(image_idx)?;
+        let scale_x = source.width as f64 / image_width as f64;
+        let scale_y = source.height as f64 / image_height as f64;
+        let win_x = (min_x / scale_x).floor().clamp(0.0, image_width as f64) as u32;
+        let win_y = (min_y / scale_y).floor().clamp(0.0, image_height as f64) as u32;
+        let win_w = ((max_x / scale_x).floor() as i64 + 1).clamp(0, image_width as i64) as u32;
+        let win_h = ((max_y / scale_y).floor() as i64 + 1).clamp(0, image_height as i64) as u32;
+        let (win_w, win_h) = (win_w.saturating_sub(win_x), win_h.saturating_sub(win_y));
+        let window = tiff.read_masked_window(win_x, win_y, win_w, win_h)?;
+
+        let values: Vec<Option<RasterValue>> = positions
+            .iter()
+            .map(|position| {
+                let (x, y) = (*position)?;
+                let (x, y) = ((x / scale_x).floor(), (y / scale_y).floor());
+                if x < win_x as f64 || y < win_y as f64 {
+                    return None;
+                }
+                let (x, y) = (x as u32 - win_x, y as u32 - win_y);
+                if x >= win_w || y >= win_h {
+                    return None;
+                }
+                match window[(y * win_w + x) as usize] {
+                    RasterValue::NoData => None,
+                    value => Some(value),
+                }
+            })
+            .collect();
+        if values.iter().all(Option::is_none) {
+            return Ok(None);
+        }
+
+        let img = match self.encoding {
+            Some(ElevationEncoding::Raw) => {
+                let buffer = ImageBuffer::from_fn(size, size, |x, y| {
+                    let value = values[(y * size + x) as usize]
+                        .map(|v| v.cast_to(SampleType::U16))
+                        .and_then(|v| u64::try_from(v).ok())
+                        .unwrap_or(0);
+                    Luma([value as u16])
+                });
+                DynamicImage::ImageLuma16(buffer)
+            }
+            Some(encoding) => DynamicImage::ImageRgba8(RgbaImage::from_fn(size, size, |x, y| {
+                // NaN heights can't be encoded
+                match values[(y * size + x) as usize]
+                    .and_then(|v| v.as_f64())
+                    .filter(|h| !h.is_nan())
+                    .and_then(|h| encoding.encode_rgb(h))
+                {
+                    Some([r, g, b]) => Rgba([r, g, b, 255]),
+                    None => Rgba([0, 0, 0, 0]),
+                }
+            })),
+            None => DynamicImage::ImageRgba8(RgbaImage::from_fn(size, size, |x, y| {
+                values[(y * size + x) as usize]
+                    .map(rgba8)
+                    .unwrap_or(Rgba([0, 0, 0, 0]))
+            })),
+        };
+        let format = match self.format {
+            TileFormat::Png => ImageFormat::Png,
+            TileFormat::Webp => ImageFormat::WebP,
+        };
+        let mut data = Vec::new();
+        img.write_to(&mut Cursor::new(&mut data), format)?;
+        Ok(Some(data))
+    }
+}
+
+/// Georeference of the full resolution source image
+struct SourceGrid {
+    /// Transformation from WebMercator into the source CRS
+    to_source: Transformer,
+    origin: [f64; 2],
+    pixel_size: [f64; 2],
+    width: u32,
+    height: u32,
+}
+
+impl SourceGrid {
+    /// Extent in source CRS
+    fn extent(&self) -> BoundingBox {
+        let x2 = self.origin[0] + self.width as f64 * self.pixel_size[0];
+        let y2 = self.origin[1] + self.height as f64 * self.pixel_size[1];
+        BoundingBox::new(
+            self.origin[0].min(x2),
+            self.origin[1].min(y2),
+            self.origin[0].max(x2),
+            self.origin[1].max(y2),
+        )
+    }
+
+    /// Full resolution pixel position of each tile pixel center in row-major order,
+    /// `None` outside of the source CRS domain
+    ///
+    /// Positions are transformed exactly on a grid with a spacing of
+    /// `APPROX_STEP` pixels and bilinear interpolated in between.
+    fn tile_positions(
+        &self,
+        bounds: &tile_grid::BoundingBox,
+        size: u32,
+    ) -> Vec<Option<(f64, f64)>> {
+        let res_x = (bounds.right - bounds.left) / size as f64;
+        let res_y = (bounds.top - bounds.bottom) / size as f64;
+        let position = |col: u32, row: u32| {
+            let x = bounds.left + (col as f64 + 0.5) * res_x;
+            let y = bounds.top - (row as f64 + 0.5) * res_y;
+            self.pixel_at(x, y)
+        };
+        // Grid nodes in tile pixels, including the last pixel
+        let nodes = (0..size.div_ceil(APPROX_STEP))
+            .map(|k| k * APPROX_STEP)
+            .chain([size.saturating_sub(1)])
+            .collect::<Vec<_>>();
+        let grid = nodes
+            .iter()
+            .flat_map(|row| nodes.iter().map(|col| position(*col, *row)))
+            .collect::<Vec<_>>();
+        let n = nodes.len();
+        // Node interval and fraction of a pixel index
+        let interval = |i: u32| {
+            let k = nodes.partition_point(|node| *node <= i).clamp(1, n - 1) - 1;
+            let span = (nodes[k + 1] - nodes[k]).max(1);
+            (k, (i - nodes[k]) as f64 / span as f64)
+        };
+        (0..size * size)
+            .map(|i| {
+                let (col, row) = (i % size, i / size);
+                let ((kx, fx), (ky, fy)) = (interval(col), interval(row));
+                let corners = [
+                    grid[ky * n + kx],
+                    grid[ky * n + kx + 1],
+                    grid[(ky + 1) * n + kx],
+                    grid[(ky + 1) * n + kx + 1],
+                ];
+                match corners {
+                    [Some(a), Some(b), Some(c), Some(d)] => {
+                        let lerp = |p: f64, q: f64, f: f64| p + f * (q - p);
+                        let top = (lerp(a.0, b.0, fx), lerp(a.1, b.1, fx));
+                        let bottom = (lerp(c.0, d.0, fx), lerp(c.1, d.1, fx));
+                        Some((lerp(top.0, bottom.0, fy), lerp(top.1, bottom.1, fy)))
+                    }
+                    // Exact transformation at the border of the CRS domain
+                    _ => position(col, row),
+                }
+            })
+            .collect()
+    }
+
+    /// Fractional pixel position of a WebMercator coordinate
+    fn pixel_at(&self, x: f64, y: f64) -> Option<(f64, f64)> {
+        let coord = self.to_source.transform((x, y)).ok()?;
+        Some((
+            (coord.x - self.origin[0]) / self.pixel_size[0],
+            (coord.y - self.origin[1]) / self.pixel_size[1],
+        ))
+    }
+}
+
+fn rgba8(value: RasterValue) -> Rgba<u8> {
+    match value {
+        RasterValue::Rgb8(r, g, b) => Rgba([r, g, b, 255]),
+        RasterValue::Rgba8(r, g, b, a) => Rgba([r, g, b, a]),
+        RasterValue::Rgb16(r, g, b) => Rgba([(r >> 8) as u8, (g >> 8) as u8, (b >> 8) as u8, 255]),
+        RasterValue::Rgba16(r, g, b, a) => Rgba([
+            (r >> 8) as u8,
+            (g >> 8) as u8,
+            (b >> 8) as u8,
+            (a >> 8) as u8,
+        ]),
+        _ => match u64::try_from(value.cast_to(SampleType::U8)) {
+            Ok(v) => Rgba([v as u8, v as u8, v as u8, 255]),
+            Err(_) => Rgba([0, 0, 0, 0]),
+        },
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::pmtiles::PmtilesRasterReader;
+    use std::fs::File;
+    use std::io::BufReader;
+
+    #[tokio::test]
+    async fn write_elevation_tiles() {
+        let img_file =
+            BufReader::new(File::open("data/tiff/scaled_int16.tif").expect("Open image file"));
+        let mut tiff = GeoTiffReader::open(img_file).expect("Open Tiff");
+        tiff.apply_scale_offset(true);
+        let path = std::env::temp_dir().join("georaster_scaled_int16.pmtiles");
+        PmtilesRasterWriter::new(4, 8)
+            .elevation_encoding(ElevationEncoding::Terrarium)
+            .write(&mut tiff, File::create(&path).unwrap())
+            .unwrap();
+
+        let pmtiles = PmtilesRasterReader::open(path.to_str().unwrap())
+            .await
+            .unwrap();
+        // Pixel (2, 0): 2.0, pixel (0, 1): 5.0
+        let height = pmtiles
+            .get_pixel_at(8, (8.25, 46.75))
+            .await
+            .unwrap()
+            .height();
+        assert!((height - 2.0).abs() < 0.01);
+        let height = pmtiles
+            .get_pixel_at(8, (7.25, 46.25))
+            .await
+            .unwrap()
+            .height();
+        assert!((height - 5.0).abs() < 0.01);
+        // NoData pixel (1, 1)
+        assert_eq!(
+            pmtiles.get_pixel_at(8, (7.75, 46.25)).await.unwrap(),
+            RasterValue::NoData
+        );
+        // Outside of raster
+        assert_eq!(
+            pmtiles.get_pixel_at(8, (10.5, 46.5)).await.unwrap(),
+            RasterValue::NoData
+        );
+        std::fs::remove_file(path).ok();
+    }
+
+    #[tokio::test]
+    async fn write_projected() {
+        // NAD27 / UTM zone 11N
+        let img_file = BufReader::new(File::open("data/tiff/utm.tif").expect("Open image file"));
+        let mut tiff = GeoTiffReader::open(img_file).expect("Open Tiff");
+        let path = std::env::temp_dir().join("georaster_utm.pmtiles");
+        PmtilesRasterWriter::new(12, 12)
+            .write(&mut tiff, File::create(&path).unwrap())
+            .unwrap();
+
+        let pmtiles = PmtilesRasterReader::open(path.to_str().unwrap())
+            .await
+            .unwrap();
+        let to_lonlat = Transformer::new(26711, 4326).unwrap();
+        let [res_x, res_y] = tiff.pixel_size().unwrap();
+        // Pixel centers have the source values
+        for (x, y) in [(10, 20), (256, 256), (500, 400)] {
+            let ul = tiff.pixel_to_coord(x, y).unwrap();
+            let center = (ul.x + res_x / 2.0, ul.y + res_y / 2.0);
+            let lonlat = to_lonlat.transform(center).unwrap();
+            let RasterValue::U8(value) = tiff.read_pixel(x, y) else {
+                panic!("RasterValue::U8(_)")
+            };
+            assert_eq!(
+                pmtiles.get_pixel_at(12, lonlat).await.unwrap(),
+                RasterValue::Rgba8(value, value, value, 255)
+            );
+        }
+        std::fs::remove_file(path).ok();
+    }
+
+    #[tokio::test]
+    async fn write_scaled_nodata() {
+        use std::io::Cursor;
+        use tiff::encoder::{colortype, TiffEncoder};
+        use tiff::tags::Tag;
+
+        // Heights 0, NoData, -50 and 0 with raw NoData 0 and offset -100
+        let mut data = Cursor::new(Vec::new());
+        let mut encoder = TiffEncoder::new(&mut data).unwrap();
+        let mut image = encoder.new_image::<colortype::Gray8>(2, 2).unwrap();
+        let directory = image.encoder();
+        directory
+            .write_tag(Tag::ModelPixelScaleTag, &[0.5, 0.5, 0.0][..])
+            .unwrap();
+        directory
+            .write_tag(Tag::ModelTiepointTag, &[0.0, 0.0, 0.0, 7.0, 47.0, 0.0][..])
+            .unwrap();
+        let geo_keys: [u16; 16] = [1, 1, 0, 3, 1024, 0, 1, 2, 1025, 0, 1, 1, 2048, 0, 1, 4326];
+        directory
+            .write_tag(Tag::GeoKeyDirectoryTag, &geo_keys[..])
+            .unwrap();
+        directory.write_tag(Tag::GdalNodata, "0").unwrap();
+        let metadata = r#"<GDALMetadata><Item name="OFFSET" sample="0" role="offset">-100</Item></GDALMetadata>"#;
+        directory.write_tag(Tag::Unknown(42112), metadata).unwrap();
+        image.write_data(&[100u8, 0, 50, 100][..]).unwrap();
+        data.set_position(0);
+        let mut tiff = GeoTiffReader::open(data).expect("Open Tiff");
+        tiff.apply_scale_offset(true);
+
+        let mut archive = Cursor::new(Vec::new());
+        PmtilesRasterWriter::new(6, 6)
+            .elevation_encoding(ElevationEncoding::Terrarium)
+            .write(&mut tiff, &mut archive)
+            .unwrap();
+        let pmtiles = PmtilesRasterReader::from_bytes(bytes::Bytes::from(archive.into_inner()))
+            .await
+            .unwrap();
+        // Height 0 equal to the raw NoData value
+        let height = pmtiles.get_height_at(6, (7.25, 46.75)).await.unwrap();
+        assert_eq!(height, 0.0);
+        assert_eq!(
+            pmtiles.get_pixel_at(6, (7.75, 46.75)).await.unwrap(),
+            RasterValue::NoData
+        );
+        let height = pmtiles.get_height_at(6, (7.25, 46.25)).await.unwrap();
+        assert_eq!(height, -50.0);
+    }
+}
@@ -0,0 +1,405 @@
+//! Vectorization of connected raster regions with equal values.
+
+use crate::{Raster, RasterValue};
+use geo_types::{Coord, LineString, MultiPolygon, Polygon};
+use std::collections::HashMap;
+
+/// Neighbourhood of connected pixels
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum Connectivity {
+    /// Pixels sharing an edge
+    #[default]
+    Four,
+    /// Pixels sharing an edge or a corner
+    Eight,
+}
+
+/// Connected pixels with equal value
+#[derive(Debug, Clone, PartialEq)]
+pub struct Region {
+    pub value: RasterValue,
+    /// Outline of the pixels, with more than one polygon for regions connected
+    /// only by corners
+    pub geometry: MultiPolygon,
+}
+
+/// Regions of connected pixels with equal value
+///
+/// NoData and NaN pixels are not part of any region. Polygons follow pixel
+/// edges, exterior rings are counter-clockwise and holes clockwise. Regions
+/// are ordered by their first pixel in row-major order.
+pub fn polygonize(raster: &Raster, connectivity: Connectivity) -> Vec<Region> {
+    let (width, height) = (raster.width as i64, raster.height as i64);
+    let neighbours: &[(i64, i64)] = match connectivity {
+        Connectivity::Four => &[(1, 0), (0, 1), (-1, 0), (0, -1)],
+        Connectivity::Eight => &[
+            (1, 0),
+            (1, 1),
+            (0, 1),
+            (-1, 1),
+            (-1, 0),
+            (-1, -1),
+            (0, -1),
+            (1, -1),
+        ],
+    };
+    let valid = |v: &RasterValue| *v != RasterValue::NoData && !v.as_f64().is_some_and(f64::is_nan);
+    let mut labels = vec![usize::MAX; raster.values.len()];
+    let mut regions = Vec::new();
+    for start in 0..raster.values.len() {
+        let value = raster.values[start];
+        if labels[start] != usize::MAX || !valid(&value) {
+            continue;
+        }
+        let id = regions.len();
+        labels[start] = id;
+        let mut pixels = vec![start];
+        let mut idx = 0;
+        while let Some(pixel) = pixels.get(idx).copied() {
+            idx += 1;
+            let (x, y) = (pixel as i64 % width, pixel as i64 / width);
+            for (dx, dy) in neighbours {
+                let (nx, ny) = (x + dx, y + dy);
+                if nx < 0 || ny < 0 || nx >= width || ny >= height {
+                    continue;
+                }
+                let neighbour = (ny * width + nx) as usize;
+                if labels[neighbour] == usize::MAX && raster.values[neighbour] == value {
+                    labels[neighbour] = id;
+                    pixels.push(neighbour);
+                }
+            }
+        }
+        let outline = Outline {
+            labels: &labels,
+            id,
+            width,
+            height,
+        };
+        regions.push(Region {
+            value,
+            geometry: outline.polygons(&pixels, raster.origin, raster.pixel_size),
+        });
+    }
+    regions
+}
+
+/// Pixel corner in grid coordinates
+type Vertex = (i64, i64);
+
+/// Pixel edge on the outline of a region, with the region on the right side
+/// in grid coordinates with y downwards
+struct Edge {
+    from: Vertex,
+    to: Vertex,
+    pixel: usize,
+}
+
+/// Outline of the pixels labeled with `id`
+struct Outline<'a> {
+    labels: &'a [usize],
+    id: usize,
+    width: i64,
+    height: i64,
+}
+
+impl Outline<'_> {
+    fn inside(&self, x: i64, y: i64) -> bool {
+        x >= 0
+            && y >= 0
+            && x < self.width
+            && y < self.height
+            && self.labels[(y * self.width + x) as usize] == self.id
+    }
+
+    fn edges(&self, pixels: &[usize]) -> Vec<Edge> {
+        let mut edges = Vec::new();
+        for pixel in pixels {
+            let (x, y) = (*pixel as i64 % self.width, *pixel as i64 / self.width);
+            let sides = [
+                ((0, -1), (x, y), (x + 1, y)),
+                ((1, 0), (x + 1, y), (x + 1, y + 1)),
+                ((0, 1), (x + 1, y + 1), (x, y + 1)),
+                ((-1, 0), (x, y + 1), (x, y)),
+            ];
+            for ((dx, dy), from, to) in sides {
+                if !self.inside(x + dx, y + dy) {
+                    edges.push(Edge {
+                        from,
+                        to,
+                        pixel: *pixel,
+                    });
+                }
+            }
+        }
+        edges
+    }
+
+    fn polygons(&self, pixels: &[usize], origin: [f64; 2], pixel_size: [f64; 2]) -> MultiPolygon {
+        let edges = self.edges(pixels);
+        let mut outgoing: HashMap<Vertex, Vec<usize>> = HashMap::new();
+        for (idx, edge) in edges.iter().enumerate() {
+            outgoing.entry(edge.from).or_default().push(idx);
+        }
+        // Next edge of a ring, turning right at vertices touched by two pixel
+        // corners, which keeps pixels connected only by corners apart
+        let next = |idx: usize| {
+            let edge = &edges[idx];
+            let (dx, dy) = (edge.to.0 - edge.from.0, edge.to.1 - edge.from.1);
+            let candidates = &outgoing[&edge.to];
+            *candidates
+                .iter()
+                .find(|c| {
+                    let c = &edges[**c];
+                    (c.to.0 - c.from.0, c.to.1 - c.from.1) == (-dy, dx)
+                })
+                .unwrap_or(&candidates[0])
+        };
+
+        let mut used = vec![false; edges.len()];
+        let mut exteriors = Vec::new();
+        let mut holes = Vec::new();
+        for start in 0..edges.len() {
+            if used[start] {
+                continue;
+            }
+            let mut ring = Vec::new();
+            let mut idx = start;
+            loop {
+                used[idx] = true;
+                ring.push(idx);
+                idx = next(idx);
+                if idx == start {
+                    break;
+                }
+            }
+            for ring in simple_rings(&edges, ring) {
+                let pixel = edges[ring[0]].pixel;
+                let ring = corners(ring.iter().map(|idx| edges[*idx].from).collect());
+                if area(&ring) > 0 {
+                    exteriors.push(ring);
+                } else {
+                    holes.push((ring, pixel));
+                }
+            }
+        }
+
+        let mut polygon_holes = vec![Vec::new(); exteriors.len()];
+        for (hole, pixel) in holes {
+            let center = (
+                (pixel as i64 % self.width) as f64 + 0.5,
+                (pixel as i64 / self.width) as f64 + 0.5,
+            );
+            // Innermost exterior for holes of polygons nested in other holes
+            let idx = if exteriors.len() == 1 {
+                0
+            } else {
+                exteriors
+                    .iter()
+                    .enumerate()
+                    .filter(|(_, ring)| contains(ring, center))
+                    .min_by_key(|(_, ring)| area(ring))
+                    .map_or(0, |(idx, _)| idx)
+            };
+            polygon_holes[idx].push(hole);
+        }
+
+        // Grid rings are clockwise in map coordinates of north-up rasters
+        let reverse = pixel_size[0] * pixel_size[1] < 0.0;
+        let line = |ring: Vec<Vertex>| {
+            let mut coords = ring
+                .iter()
+                .map(|(x, y)| Coord {
+                    x: origin[0] + *x as f64 * pixel_size[0],
+                    y: origin[1] + *y as f64 * pixel_size[1],
+                })
+                .collect::<Vec<_>>();
+            coords.push(coords[0]);
+            if reverse {
+                coords.reverse();
+            }
+            LineString::new(coords)
+        };
+        exteriors
+            .into_iter()
+            .zip(polygon_holes)
+            .map(|(exterior, holes)| {
+                Polygon::new(line(exterior), holes.into_iter().map(line).collect())
+            })
+            .collect()
+    }
+}
+
+/// Split a ring of edges at vertices visited more than once
+///
+/// An exterior ring touching a hole at a pixel corner is traced as one ring,
+/// which is split into the exterior and the hole.
+fn simple_rings(edges: &[Edge], ring: Vec<usize>) -> Vec<Vec<usize>> {
+    let mut rings = Vec::new();
+    let mut stack: Vec<usize> = Vec::new();
+    let mut visited = HashMap::new();
+    for idx in ring {
+        let vertex = edges[idx].from;
+        if let Some(pos) = visited.get(&vertex) {
+            let ring = stack.split_off(*pos);
+            for idx in &ring {
+                visited.remove(&edges[*idx].from);
+            }
+            rings.push(ring);
+        }
+        visited.insert(vertex, stack.len());
+        stack.push(idx);
+    }
+    rings.push(stack);
+    rings
+}
+
+/// Ring vertices without intermediate points on straight edges
+fn corners(ring: Vec<Vertex>) -> Vec<Vertex> {
+    let n = ring.len();
+    (0..n)
+        .filter(|i| {
+            let (prev, cur, next) = (ring[(i + n - 1) % n], ring[*i], ring[(i + 1) % n]);
+            (cur.0 - prev.0, cur.1 - prev.1) != (next.0 - cur.0, next.1 - cur.1)
+        })
+        .map(|i| ring[i])
+        .collect()
+}
+
+/// Twice the signed area of a ring, positive for exterior rings in grid coordinates
+fn area(ring: &[Vertex]) -> i64 {
+    let n = ring.len();
+    (0..n)
+        .map(|i| {
+            let (a, b) = (ring[i], ring[(i + 1) % n]);
+            a.0 * b.1 - b.0 * a.1
+        })
+        .sum()
+}
+
+/// Even-odd test of a point, which is not on the ring
+fn contains(ring: &[Vertex], (x, y): (f64, f64)) -> bool {
+    let n = ring.len();
+    let mut inside = false;
+    for i in 0..n {
+        let (a, b) = (ring[i], ring[(i + 1) % n]);
+        let (ay, by) = (a.1 as f64, b.1 as f64);
+        if (ay > y) != (by > y) {
+            let cx = a.0 as f64 + (y - ay) / (by - ay) * (b.0 - a.0) as f64;
+            if x < cx {
+                inside = !inside;
+            }
+        }
+    }
+    inside
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn raster(rows: &[&[u8]]) -> Raster {
+        let (width, height) = (rows[0].len() as u32, rows.len() as u32);
+        let mut raster = Raster::new(width, height, [0.0, height as f64], [1.0, -1.0]);
+        for (y, row) in rows.iter().enumerate() {
+            for (x, value) in row.iter().enumerate() {
+                if *value != 9 {
+                    raster.set(x as u32, y as u32, RasterValue::U8(*value));
+                }
+            }
+        }
+        raster
+    }
+
+    fn coords(ring: &LineString) -> Vec<(f64, f64)> {
+        ring.coords().map(|c| (c.x, c.y)).collect()
+    }
+
+    #[test]
+    fn ring_with_hole() {
+        let regions = polygonize(
+            &raster(&[&[1, 1, 1], &[1, 2, 1], &[1, 1, 1]]),
+            Connectivity::Four,
+        );
+        assert_eq!(regions.len(), 2);
+        assert_eq!(regions[0].value, RasterValue::U8(1));
+        let polygon = &regions[0].geometry.0[0];
+        // Counter-clockwise exterior
+        assert_eq!(
+            coords(polygon.exterior()),
+            [(0.0, 3.0), (0.0, 0.0), (3.0, 0.0), (3.0, 3.0), (0.0, 3.0)]
+        );
+        // Clockwise hole
+        assert_eq!(
+            coords(&polygon.interiors()[0]),
+            [(2.0, 2.0), (2.0, 1.0), (1.0, 1.0), (1.0, 2.0), (2.0, 2.0)]
+        );
+        assert_eq!(regions[1].value, RasterValue::U8(2));
+        assert_eq!(regions[1].geometry.0[0].interiors().len(), 0);
+
+        // Hole touching the exterior at a corner
+        let raster = raster(&[&[1, 1, 0], &[1, 0, 1], &[1, 1, 1]]);
+        let regions = polygonize(&raster, Connectivity::Four);
+        assert_eq!(regions.len(), 3);
+        let polygon = &regions[0].geometry.0[0];
+        assert_eq!(regions[0].geometry.0.len(), 1);
+        assert_eq!(polygon.exterior().0.len(), 7);
+        assert_eq!(polygon.interiors().len(), 1);
+        assert_eq!(polygon.interiors()[0].0.len(), 5);
+        let regions = polygonize(&raster, Connectivity::Eight);
+        assert_eq!(regions.len(), 2);
+        assert_eq!(regions[1].geometry.0.len(), 2);
+    }
+
+    #[test]
+    fn connectivity() {
+        // Diagonal pixels and NoData (9)
+        let raster = raster(&[&[1, 0, 9], &[0, 1, 1], &[9, 0, 0]]);
+        let regions = polygonize(&raster, Connectivity::Four);
+        let values = regions.iter().map(|r| r.value).collect::<Vec<_>>();
+        use RasterValue::U8;
+        assert_eq!(values, [U8(1), U8(0), U8(0), U8(1), U8(0)]);
+
+        let regions = polygonize(&raster, Connectivity::Eight);
+        assert_eq!(regions.len(), 2);
+        // Pixels touching at corners are separate polygons
+        assert_eq!(regions[0].geometry.0.len(), 2);
+        assert_eq!(
+            coords(regions[0].geometry.0[1].exterior()),
+            [(1.0, 2.0), (1.0, 1.0), (3.0, 1.0), (3.0, 2.0), (1.0, 2.0)]
+        );
+        // Value 0 pixels (1, 0) and (0, 1) touching (1, 2) and (2, 2)
+        assert_eq!(regions[1].value, U8(0));
+        assert_eq!(regions[1].geometry.0.len(), 3);
+    }
+
+    #[test]
+    fn nested_holes() {
+        // Ring of 1 with a hole containing a ring of 1 connected by the corner (5, 5)
+        let raster = raster(&[
+            &[1, 1, 1, 1, 1, 1, 1],
+            &[1, 0, 0, 0, 0, 0, 1],
+            &[1, 0, 1, 1, 1, 0, 1],
+            &[1, 0, 1, 2, 1, 0, 1],
+            &[1, 0, 1, 1, 1, 0, 1],
+            &[1, 0, 0, 0, 0, 1, 1],
+            &[1, 1, 1, 1, 1, 1, 1],
+        ]);
+        let regions = polygonize(&raster, Connectivity::Eight);
+        assert_eq!(regions[0].value, RasterValue::U8(1));
+        let polygons = &regions[0].geometry.0;
+        assert_eq!(polygons.len(), 2);
+        assert_eq!(polygons[0].exterior().0.len(), 5);
+        assert_eq!(polygons[0].interiors().len(), 1);
+        assert_eq!(polygons[0].interiors()[0].0.len(), 7);
+        // Hole of the inner ring
+        assert_eq!(
+            coords(polygons[1].exterior()),
+            [(5.0, 2.0), (5.0, 5.0), (2.0, 5.0), (2.0, 2.0), (5.0, 2.0)]
+        );
+        assert_eq!(
+            coords(&polygons[1].interiors()[0]),
+            [(3.0, 3.0), (3.0, 4.0), (4.0, 4.0), (4.0, 3.0), (3.0, 3.0)]
+        );
+    }
+}
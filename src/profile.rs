@@ -0,0 +1,198 @@
+//! Raster values along a line, e.g. elevation profiles.
+
+use crate::geo::EARTH_RADIUS;
+use crate::{Coordinate, GeorasterResult};
+
+/// Sampling of a profile line
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ProfileStep {
+    /// Samples in a fixed distance along the line
+    ///
+    /// The distance is in metres for geographic coordinates, in CRS units otherwise.
+    Distance(f64),
+    /// Samples at every crossing of a pixel boundary
+    PixelCrossing,
+}
+
+/// Sample of a profile
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ProfilePoint {
+    /// Distance from the start of the line
+    pub distance: f64,
+    pub coord: Coordinate,
+    /// Bilinear interpolated value, `None` for NoData
+    pub value: Option<f64>,
+}
+
+/// Raster values along a line
+///
+/// ```rust
+/// use georaster::geotiff::GeoTiffReader;
+/// use georaster::profile::ProfileStep;
+/// use georaster::Coordinate;
+/// use std::{fs::File, io::BufReader};
+///
+/// let img_file = BufReader::new(File::open("data/tiff/scaled_int16.tif").unwrap());
+/// let mut tiff = GeoTiffReader::open(img_file).unwrap();
+/// let line = [Coordinate { x: 7.2, y: 46.8 }, Coordinate { x: 8.8, y: 45.2 }];
+/// let profile = tiff.profile(&line, ProfileStep::Distance(1000.0)).unwrap();
+/// println!(
+///     "{:.0} m, ascent {:.0}, descent {:.0}",
+///     profile.length(),
+///     profile.ascent(),
+///     profile.descent()
+/// );
+/// ```
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Profile {
+    pub points: Vec<ProfilePoint>,
+}
+
+impl Profile {
+    /// Length of the line
+    pub fn length(&self) -> f64 {
+        self.points.last().map(|p| p.distance).unwrap_or(0.0)
+    }
+
+    /// Cumulative ascent, NoData gaps are bridged
+    pub fn ascent(&self) -> f64 {
+        self.changes().filter(|d| *d > 0.0).sum()
+    }
+
+    /// Cumulative descent as positive value, NoData gaps are bridged
+    pub fn descent(&self) -> f64 {
+        -self.changes().filter(|d| *d < 0.0).sum::<f64>()
+    }
+
+    /// Value differences between consecutive samples with value
+    fn changes(&self) -> impl Iterator<Item = f64> + '_ {
+        let values = self.points.iter().filter_map(|p| p.value);
+        values.clone().zip(values.skip(1)).map(|(a, b)| b - a)
+    }
+}
+
+/// Distance between two coordinates, great circle distance in metres for geographic coordinates
+fn distance(a: Coordinate, b: Coordinate, geographic: bool) -> f64 {
+    if !geographic {
+        return (b.x - a.x).hypot(b.y - a.y);
+    }
+    let (lat1, lat2) = (a.y.to_radians(), b.y.to_radians());
+    let dlat = lat2 - lat1;
+    let dlon = (b.x - a.x).to_radians();
+    let h = (dlat / 2.0).sin().powi(2) + lat1.cos() * lat2.cos() * (dlon / 2.0).sin().powi(2);
+    2.0 * EARTH_RADIUS * h.sqrt().asin()
+}
+
+/// Fractions of the segment `a`-`b` crossing a grid line at `origin + k * size`
+fn grid_crossings(a: f64, b: f64, origin: f64, size: f64) -> impl Iterator<Item = f64> {
+    let (ka, kb) = ((a - origin) / size, (b - origin) / size);
+    let k0 = ka.min(kb).floor() as i64 + 1;
+    let k1 = ka.max(kb).ceil() as i64 - 1;
+    (k0..=k1).map(move |k| (origin + k as f64 * size - a) / (b - a))
+}
+
+/// Sample positions `(distance, coordinate)` along `line`, grouped by line segment
+///
+/// Pixel crossings are computed in the grid CRS of the raster with upper left
+/// corner `origin` and `pixel_size`. `to_grid` projects line coordinates into
+/// the grid CRS.
+pub(crate) fn sample_positions(
+    line: &[Coordinate],
+    step: ProfileStep,
+    geographic: bool,
+    origin: [f64; 2],
+    pixel_size: [f64; 2],
+    to_grid: impl Fn(Coordinate) -> GeorasterResult<Coordinate>,
+) -> GeorasterResult<Vec<Vec<(f64, Coordinate)>>> {
+    let mut segments = Vec::new();
+    let mut start = 0.0;
+    for (idx, pair) in line.windows(2).enumerate() {
+        let (a, b) = (pair[0], pair[1]);
+        let length = distance(a, b, geographic);
+        let mut fractions = match step {
+            ProfileStep::Distance(step) => {
+                let n = (length / step).ceil().max(1.0) as usize;
+                (0..n).map(|i| i as f64 / n as f64).collect::<Vec<_>>()
+            }
+            ProfileStep::PixelCrossing => {
+                let (ga, gb) = (to_grid(a)?, to_grid(b)?);
+                let mut fractions = vec![0.0];
+                fractions.extend(grid_crossings(ga.x, gb.x, origin[0], pixel_size[0]));
+                fractions.extend(grid_crossings(ga.y, gb.y, origin[1], pixel_size[1]));
+                fractions.sort_by(f64::total_cmp);
+                fractions.dedup();
+                fractions
+            }
+        };
+        // End point of the line
+        if idx == line.len() - 2 {
+            fractions.push(1.0);
+        }
+        let samples = fractions
+            .into_iter()
+            .map(|t| {
+                let coord = Coordinate {
+                    x: a.x + t * (b.x - a.x),
+                    y: a.y + t * (b.y - a.y),
+                };
+                (start + t * length, coord)
+            })
+            .collect();
+        segments.push(samples);
+        start += length;
+    }
+    if let [coord] = line {
+        segments.push(vec![(0.0, *coord)]);
+    }
+    Ok(segments)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn sampling() {
+        let line = [
+            Coordinate { x: 0.5, y: 0.5 },
+            Coordinate { x: 3.5, y: 0.5 },
+            Coordinate { x: 3.5, y: 2.5 },
+        ];
+        let positions = |step| {
+            sample_positions(&line, step, false, [0.0, 4.0], [1.0, -1.0], Ok)
+                .unwrap()
+                .concat()
+        };
+        let samples = positions(ProfileStep::Distance(1.0));
+        assert_eq!(samples.len(), 6);
+        assert_eq!(samples[4], (4.0, Coordinate { x: 3.5, y: 1.5 }));
+        assert_eq!(samples[5], (5.0, Coordinate { x: 3.5, y: 2.5 }));
+
+        let samples = positions(ProfileStep::PixelCrossing);
+        let distances = samples.iter().map(|s| s.0).collect::<Vec<_>>();
+        assert_eq!(distances, [0.0, 0.5, 1.5, 2.5, 3.0, 3.5, 4.5, 5.0]);
+
+        let profile = Profile {
+            points: [Some(10.0), Some(12.0), None, Some(11.0), Some(15.0)]
+                .into_iter()
+                .enumerate()
+                .map(|(i, value)| ProfilePoint {
+                    distance: i as f64,
+                    coord: Coordinate::default(),
+                    value,
+                })
+                .collect(),
+        };
+        assert_eq!(profile.length(), 4.0);
+        assert_eq!(profile.ascent(), 6.0);
+        assert_eq!(profile.descent(), 1.0);
+
+        // One degree of latitude
+        let d = distance(
+            Coordinate { x: 8.0, y: 46.0 },
+            Coordinate { x: 8.0, y: 47.0 },
+            true,
+        );
+        assert!((d - 111_195.0).abs() < 1.0, "{d}");
+    }
+}
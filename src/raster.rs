@@ -0,0 +1,296 @@
+//! In-memory raster and common raster source traits.
+
+use crate::geo::BoundingBox;
+use crate::{Coordinate, GeorasterError, GeorasterResult, RasterValue};
+use std::future::Future;
+
+/// Raster data source with synchronous access
+///
+/// Coordinates and bounding boxes are in the CRS of the raster.
+pub trait GeoRaster {
+    /// Extent in raster CRS with the EPSG code of the raster
+    fn extent(&self) -> Option<BoundingBox>;
+    /// EPSG code of raster CRS
+    fn epsg_code(&self) -> Option<u32>;
+    /// Pixel width and height in CRS units
+    fn resolution(&self) -> Option<[f64; 2]>;
+    /// Number of bands
+    fn band_count(&self) -> usize;
+    /// Value of pixel containing `coord`, `RasterValue::NoData` outside of the raster
+    fn sample(&mut self, coord: Coordinate) -> GeorasterResult<RasterValue>;
+    /// Read all pixels intersecting `bbox`
+    fn read_extent(&mut self, bbox: &BoundingBox) -> GeorasterResult<Raster>;
+    /// Read all pixels intersecting `bbox` with a pixel size close to `resolution`
+    ///
+    /// Sources with overviews read from the coarsest overview which is not coarser
+    /// than `resolution`. Returns `GeorasterError::WindowTooLarge` if the window
+    /// exceeds `max_pixels`.
+    fn read_extent_at(
+        &mut self,
+        bbox: &BoundingBox,
+        _resolution: f64,
+        max_pixels: u64,
+    ) -> GeorasterResult<Raster> {
+        check_window_size(self, bbox, max_pixels)?;
+        self.read_extent(bbox)
+    }
+}
+
+/// Raster data source with asynchronous access
+///
+/// Coordinates and bounding boxes are in the CRS of the raster.
+pub trait AsyncGeoRaster {
+    /// Extent in raster CRS with the EPSG code of the raster
+    fn extent(&self) -> Option<BoundingBox>;
+    /// EPSG code of raster CRS
+    fn epsg_code(&self) -> Option<u32>;
+    /// Pixel width and height in CRS units
+    fn resolution(&self) -> Option<[f64; 2]>;
+    /// Number of bands
+    fn band_count(&self) -> usize;
+    /// Value of pixel containing `coord`, `RasterValue::NoData` outside of the raster
+    fn sample(
+        &self,
+        coord: Coordinate,
+    ) -> impl Future<Output = GeorasterResult<RasterValue>> + Send;
+    /// Read all pixels intersecting `bbox`
+    fn read_extent(
+        &self,
+        bbox: &BoundingBox,
+    ) -> impl Future<Output = GeorasterResult<Raster>> + Send;
+}
+
+/// Georeferenced raster with values in memory
+#[derive(Debug, Clone, PartialEq)]
+pub struct Raster {
+    pub width: u32,
+    pub height: u32,
+    /// Values in row-major order
+    pub values: Vec<RasterValue>,
+    /// Upper left corner of the upper left pixel
+    pub origin: [f64; 2],
+    /// Pixel size, negative in y direction for north-up rasters
+    pub pixel_size: [f64; 2],
+}
+
+impl Raster {
+    /// Create raster filled with `RasterValue::NoData`
+    pub fn new(width: u32, height: u32, origin: [f64; 2], pixel_size: [f64; 2]) -> Self {
+        Raster {
+            width,
+            height,
+            values: vec![RasterValue::NoData; width as usize * height as usize],
+            origin,
+            pixel_size,
+        }
+    }
+
+    /// Value at pixel position. Returns `RasterValue::NoData` outside of the raster.
+    pub fn get(&self, x: u32, y: u32) -> RasterValue {
+        if x >= self.width || y >= self.height {
+            return RasterValue::NoData;
+        }
+        self.values[y as usize * self.width as usize + x as usize]
+    }
+
+    /// Set value at pixel position. Positions outside of the raster are ignored.
+    pub fn set(&mut self, x: u32, y: u32, value: RasterValue) {
+        if x < self.width && y < self.height {
+            self.values[y as usize * self.width as usize + x as usize] = value;
+        }
+    }
+
+    /// Pixel containing `coord`
+    ///
+    /// Returns the `None` variant for coordinates outside of the raster.
+    pub fn coord_to_pixel(&self, coord: impl Into<Coordinate>) -> Option<(u32, u32)> {
+        let coord = coord.into();
+        let px = ((coord.x - self.origin[0]) / self.pixel_size[0]).floor();
+        let py = ((coord.y - self.origin[1]) / self.pixel_size[1]).floor();
+        if px < 0.0 || py < 0.0 || px >= self.width as f64 || py >= self.height as f64 {
+            return None;
+        }
+        Some((px as u32, py as u32))
+    }
+
+    /// Upper left corner of pixel
+    pub fn pixel_to_coord(&self, x: u32, y: u32) -> Coordinate {
+        Coordinate {
+            x: x as f64 * self.pixel_size[0] + self.origin[0],
+            y: y as f64 * self.pixel_size[1] + self.origin[1],
+        }
+    }
+
+    /// Bilinear interpolation of the 2x2 pixels with centers around `coord`
+    ///
+    /// NoData and non-numeric pixels are skipped. Returns the `None` variant
+    /// if none of the pixels has a value.
+    pub fn interpolate(&self, coord: impl Into<Coordinate>) -> Option<f64> {
+        let (fx, fy) = self.pixel_pos(coord.into());
+        let (x0, y0) = (fx.floor() as i64, fy.floor() as i64);
+        let (tx, ty) = (fx - x0 as f64, fy - y0 as f64);
+        let mut sum = 0.0;
+        let mut weights = 0.0;
+        for (dx, dy, weight) in [
+            (0, 0, (1.0 - tx) * (1.0 - ty)),
+            (1, 0, tx * (1.0 - ty)),
+            (0, 1, (1.0 - tx) * ty),
+            (1, 1, tx * ty),
+        ] {
+            match self.pixel_at(x0 + dx, y0 + dy).as_f64() {
+                Some(v) if !v.is_nan() => {
+                    sum += v * weight;
+                    weights += weight;
+                }
+                _ => {}
+            }
+        }
+        (weights > 0.0).then(|| sum / weights)
+    }
+
+    /// Fractional pixel position relative to pixel centers
+    pub(crate) fn pixel_pos(&self, coord: Coordinate) -> (f64, f64) {
+        (
+            (coord.x - self.origin[0]) / self.pixel_size[0] - 0.5,
+            (coord.y - self.origin[1]) / self.pixel_size[1] - 0.5,
+        )
+    }
+
+    /// Pixel value, `RasterValue::NoData` outside of the raster
+    pub(crate) fn pixel_at(&self, x: i64, y: i64) -> RasterValue {
+        if x < 0 || y < 0 {
+            return RasterValue::NoData;
+        }
+        self.get(x as u32, y as u32)
+    }
+}
+
+impl GeoRaster for Raster {
+    fn extent(&self) -> Option<BoundingBox> {
+        let ul = self.pixel_to_coord(0, 0);
+        let lr = self.pixel_to_coord(self.width, self.height);
+        Some(BoundingBox::new(
+            ul.x.min(lr.x),
+            ul.y.min(lr.y),
+            ul.x.max(lr.x),
+            ul.y.max(lr.y),
+        ))
+    }
+
+    fn epsg_code(&self) -> Option<u32> {
+        None
+    }
+
+    fn resolution(&self) -> Option<[f64; 2]> {
+        Some([self.pixel_size[0].abs(), self.pixel_size[1].abs()])
+    }
+
+    fn band_count(&self) -> usize {
+        1
+    }
+
+    fn sample(&mut self, coord: Coordinate) -> GeorasterResult<RasterValue> {
+        Ok(self
+            .coord_to_pixel(coord)
+            .map(|(x, y)| self.get(x, y))
+            .unwrap_or(RasterValue::NoData))
+    }
+
+    fn read_extent(&mut self, bbox: &BoundingBox) -> GeorasterResult<Raster> {
+        let (x0, y0, x1, y1) =
+            pixel_window(bbox, self.origin, self.pixel_size, self.width, self.height);
+        let ul = self.pixel_to_coord(x0, y0);
+        let mut raster = Raster::new(x1 - x0, y1 - y0, [ul.x, ul.y], self.pixel_size);
+        for y in y0..y1 {
+            for x in x0..x1 {
+                raster.set(x - x0, y - y0, self.get(x, y));
+            }
+        }
+        Ok(raster)
+    }
+}
+
+/// Pixel window `(x0, y0, x1, y1)` of a raster grid intersecting `bbox`
+pub(crate) fn pixel_window(
+    bbox: &BoundingBox,
+    origin: [f64; 2],
+    pixel_size: [f64; 2],
+    width: u32,
+    height: u32,
+) -> (u32, u32, u32, u32) {
+    let xa = (bbox.left - origin[0]) / pixel_size[0];
+    let xb = (bbox.right - origin[0]) / pixel_size[0];
+    let ya = (bbox.top - origin[1]) / pixel_size[1];
+    let yb = (bbox.bottom - origin[1]) / pixel_size[1];
+    let x0 = xa.min(xb).floor().clamp(0.0, width as f64) as u32;
+    let x1 = xa.max(xb).ceil().clamp(0.0, width as f64) as u32;
+    let y0 = ya.min(yb).floor().clamp(0.0, height as f64) as u32;
+    let y1 = ya.max(yb).ceil().clamp(0.0, height as f64) as u32;
+    (x0, y0, x1.max(x0), y1.max(y0))
+}
+
+/// Return `GeorasterError::WindowTooLarge` if reading `bbox` from `src` exceeds `max_pixels`
+pub(crate) fn check_window_size<S: GeoRaster + ?Sized>(
+    src: &S,
+    bbox: &BoundingBox,
+    max_pixels: u64,
+) -> GeorasterResult<()> {
+    let [res_x, res_y] = src.resolution().ok_or(GeorasterError::NoGeoreference)?;
+    let window = match src.extent() {
+        Some(extent) => bbox.intersection(&extent),
+        None => Some(*bbox),
+    };
+    let Some(window) = window else {
+        return Ok(());
+    };
+    let pixels = ((window.width() / res_x.abs()).ceil() + 1.0)
+        * ((window.height() / res_y.abs()).ceil() + 1.0);
+    if pixels > max_pixels as f64 {
+        return Err(GeorasterError::WindowTooLarge(pixels as u64, max_pixels));
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn raster() {
+        let mut raster = Raster::new(4, 2, [10.0, 50.0], [0.5, -0.5]);
+        raster.set(3, 1, RasterValue::U8(7));
+        raster.set(4, 1, RasterValue::U8(8));
+        assert_eq!(raster.get(3, 1), RasterValue::U8(7));
+        assert_eq!(raster.get(4, 1), RasterValue::NoData);
+        assert_eq!(raster.coord_to_pixel((11.6, 49.4)), Some((3, 1)));
+        assert_eq!(raster.coord_to_pixel((12.1, 49.4)), None);
+        assert_eq!(raster.pixel_to_coord(3, 1), Coordinate { x: 11.5, y: 49.5 });
+        raster.set(2, 1, RasterValue::U8(5));
+        assert_eq!(raster.interpolate((11.5, 49.25)), Some(6.0));
+        assert_eq!(raster.interpolate((11.5, 49.5)), Some(6.0));
+        assert_eq!(raster.interpolate((10.25, 49.75)), None);
+
+        assert_eq!(
+            raster.extent(),
+            Some(BoundingBox::new(10.0, 49.0, 12.0, 50.0))
+        );
+        assert_eq!(
+            raster.sample(Coordinate { x: 11.6, y: 49.4 }).unwrap(),
+            RasterValue::U8(7)
+        );
+        let window = raster
+            .read_extent(&BoundingBox::new(11.2, 48.0, 13.0, 49.7))
+            .unwrap();
+        assert_eq!((window.width, window.height), (2, 2));
+        assert_eq!(window.origin, [11.0, 50.0]);
+        assert_eq!(window.get(1, 1), RasterValue::U8(7));
+
+        let bbox = BoundingBox::new(11.2, 48.0, 13.0, 49.7);
+        let window = raster.read_extent_at(&bbox, 0.5, 9).unwrap();
+        assert_eq!((window.width, window.height), (2, 2));
+        assert!(matches!(
+            raster.read_extent_at(&bbox, 0.5, 8),
+            Err(GeorasterError::WindowTooLarge(9, 8))
+        ));
+    }
+}
@@ -0,0 +1,425 @@
+//! Rasterization of `geo_types` geometries onto a raster grid.
+
+use crate::geo::polygon_spans;
+use crate::raster::pixel_window;
+use crate::{BoundingBox, Coordinate, GeorasterResult, Raster, RasterValue, SampleType};
+use geo_types::{Coord, Geometry, Line, LineString, Polygon};
+use std::collections::BTreeSet;
+
+/// Combination of burn values of overlapping geometries
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum MergeAlg {
+    /// Value of the last geometry
+    #[default]
+    Replace,
+    /// Sum of the values
+    Add,
+    /// Maximum of the values
+    Max,
+}
+
+/// Burns geometries into a raster grid
+///
+/// ```rust
+/// use georaster::rasterize::Rasterizer;
+/// use georaster::{RasterValue, SampleType};
+/// use geo_types::{point, Geometry};
+///
+/// let point = Geometry::from(point! { x: 7.2, y: 46.8 });
+/// let raster = Rasterizer::new(2, 2, [7.0, 47.0], [0.5, -0.5])
+///     .sample_type(SampleType::U8)
+///     .rasterize([(&point, 1.0)])
+///     .unwrap();
+/// assert_eq!(raster.get(0, 0), RasterValue::U8(1));
+/// assert_eq!(raster.get(1, 1), RasterValue::NoData);
+/// ```
+#[derive(Debug, Clone)]
+pub struct Rasterizer {
+    width: u32,
+    height: u32,
+    origin: [f64; 2],
+    pixel_size: [f64; 2],
+    all_touched: bool,
+    merge: MergeAlg,
+    sample_type: SampleType,
+    fill: Option<f64>,
+}
+
+impl Rasterizer {
+    /// Grid with `width` x `height` pixels and upper left corner at `origin`
+    pub fn new(width: u32, height: u32, origin: [f64; 2], pixel_size: [f64; 2]) -> Self {
+        Rasterizer {
+            width,
+            height,
+            origin,
+            pixel_size,
+            all_touched: false,
+            merge: MergeAlg::default(),
+            sample_type: SampleType::F64,
+            fill: None,
+        }
+    }
+
+    /// Burn all pixels touched by lines and polygons
+    ///
+    /// By default, lines burn one pixel per row or column and polygons burn
+    /// pixels with their center inside.
+    pub fn all_touched(mut self, all_touched: bool) -> Self {
+        self.all_touched = all_touched;
+        self
+    }
+
+    /// Set merge rule for overlapping geometries
+    pub fn merge(mut self, merge: MergeAlg) -> Self {
+        self.merge = merge;
+        self
+    }
+
+    /// Set sample type of the output raster (default `F64`)
+    pub fn sample_type(mut self, sample_type: SampleType) -> Self {
+        self.sample_type = sample_type;
+        self
+    }
+
+    /// Set initial value of all pixels, instead of `RasterValue::NoData`
+    pub fn fill(mut self, value: f64) -> Self {
+        self.fill = Some(value);
+        self
+    }
+
+    /// Raster with `shapes` burned in with their values
+    ///
+    /// Shapes are burned in order, each shape at most once into a pixel.
+    /// Pixels without shape and fill value are `RasterValue::NoData`.
+    /// Returns `GeorasterError::ValueRange` for burned or fill values, which
+    /// can't be represented in the sample type.
+    pub fn rasterize<'a>(
+        &self,
+        shapes: impl IntoIterator<Item = (&'a Geometry, f64)>,
+    ) -> GeorasterResult<Raster> {
+        let mut values = vec![self.fill; self.width as usize * self.height as usize];
+        for (geometry, value) in shapes {
+            let mut pixels = BTreeSet::new();
+            self.burn(geometry, &mut pixels);
+            for idx in pixels {
+                let pixel = &mut values[idx];
+                *pixel = Some(match (self.merge, *pixel) {
+                    (MergeAlg::Add, Some(current)) => current + value,
+                    (MergeAlg::Max, Some(current)) => current.max(value),
+                    _ => value,
+                });
+            }
+        }
+        let mut raster = Raster::new(self.width, self.height, self.origin, self.pixel_size);
+        for (pixel, value) in raster.values.iter_mut().zip(values) {
+            if let Some(value) = value {
+                *pixel = RasterValue::F64(value).try_cast_to(self.sample_type)?;
+            }
+        }
+        Ok(raster)
+    }
+
+    /// Collect indices of pixels covered by `geometry`
+    fn burn(&self, geometry: &Geometry, pixels: &mut BTreeSet<usize>) {
+        match geometry {
+            Geometry::Point(point) => self.burn_point(point.0, pixels),
+            Geometry::MultiPoint(points) => {
+                for point in points {
+                    self.burn_point(point.0, pixels);
+                }
+            }
+            Geometry::Line(line) => self.burn_line(*line, pixels),
+            Geometry::LineString(line) => self.burn_line_string(line, pixels),
+            Geometry::MultiLineString(lines) => {
+                for line in lines {
+                    self.burn_line_string(line, pixels);
+                }
+            }
+            Geometry::Polygon(polygon) => self.burn_polygons(std::slice::from_ref(polygon), pixels),
+            Geometry::MultiPolygon(polygons) => self.burn_polygons(&polygons.0, pixels),
+            Geometry::Rect(rect) => self.burn_polygons(&[rect.to_polygon()], pixels),
+            Geometry::Triangle(triangle) => self.burn_polygons(&[triangle.to_polygon()], pixels),
+            Geometry::GeometryCollection(collection) => {
+                for geometry in collection {
+                    self.burn(geometry, pixels);
+                }
+            }
+        }
+    }
+
+    /// Position in pixel units from the upper left corner
+    fn pixel_pos(&self, coord: Coord) -> (f64, f64) {
+        (
+            (coord.x - self.origin[0]) / self.pixel_size[0],
+            (coord.y - self.origin[1]) / self.pixel_size[1],
+        )
+    }
+
+    fn insert(&self, x: i64, y: i64, pixels: &mut BTreeSet<usize>) {
+        if x >= 0 && y >= 0 && x < self.width as i64 && y < self.height as i64 {
+            pixels.insert(y as usize * self.width as usize + x as usize);
+        }
+    }
+
+    fn burn_point(&self, coord: Coord, pixels: &mut BTreeSet<usize>) {
+        let (x, y) = self.pixel_pos(coord);
+        self.insert(x.floor() as i64, y.floor() as i64, pixels);
+    }
+
+    fn burn_line_string(&self, line: &LineString, pixels: &mut BTreeSet<usize>) {
+        match line.0[..] {
+            [coord] => self.burn_point(coord, pixels),
+            _ => {
+                for line in line.lines() {
+                    self.burn_line(line, pixels);
+                }
+            }
+        }
+    }
+
+    fn burn_line(&self, line: Line, pixels: &mut BTreeSet<usize>) {
+        let (x0, y0) = self.pixel_pos(line.start);
+        let (x1, y1) = self.pixel_pos(line.end);
+        let Some(((x0, y0), (x1, y1))) = self.clip((x0, y0), (x1, y1)) else {
+            return;
+        };
+        if self.all_touched {
+            self.traverse((x0, y0), (x1, y1), pixels);
+        } else {
+            // One pixel per step along the major axis
+            let n = (x1 - x0).abs().max((y1 - y0).abs()).ceil().max(1.0) as usize;
+            for i in 0..=n {
+                let t = i as f64 / n as f64;
+                let (x, y) = (x0 + t * (x1 - x0), y0 + t * (y1 - y0));
+                self.insert(x.floor() as i64, y.floor() as i64, pixels);
+            }
+        }
+    }
+
+    /// Segment clipped to the grid in pixel units (Liang-Barsky)
+    fn clip(&self, (x0, y0): (f64, f64), (x1, y1): (f64, f64)) -> Option<((f64, f64), (f64, f64))> {
+        let (dx, dy) = (x1 - x0, y1 - y0);
+        let (mut t0, mut t1) = (0.0f64, 1.0f64);
+        let bounds = [
+            (-dx, x0),
+            (dx, self.width as f64 - x0),
+            (-dy, y0),
+            (dy, self.height as f64 - y0),
+        ];
+        for (p, q) in bounds {
+            if p == 0.0 {
+                if q < 0.0 {
+                    return None;
+                }
+            } else {
+                let t = q / p;
+                if p < 0.0 {
+                    t0 = t0.max(t);
+                } else {
+                    t1 = t1.min(t);
+                }
+            }
+        }
+        (t0 <= t1).then_some(((x0 + t0 * dx, y0 + t0 * dy), (x0 + t1 * dx, y0 + t1 * dy)))
+    }
+
+    /// All pixels crossed by a segment in pixel units (Amanatides-Woo)
+    fn traverse(&self, (x0, y0): (f64, f64), (x1, y1): (f64, f64), pixels: &mut BTreeSet<usize>) {
+        let (mut x, mut y) = (x0.floor() as i64, y0.floor() as i64);
+        let (end_x, end_y) = (x1.floor() as i64, y1.floor() as i64);
+        let (dx, dy) = (x1 - x0, y1 - y0);
+        let (step_x, step_y) = (dx.signum() as i64, dy.signum() as i64);
+        let boundary = |pos: f64, cell: i64, step: i64| (cell + (step > 0) as i64) as f64 - pos;
+        let (mut t_x, delta_x) = if dx != 0.0 {
+            (boundary(x0, x, step_x) / dx, 1.0 / dx.abs())
+        } else {
+            (f64::INFINITY, f64::INFINITY)
+        };
+        let (mut t_y, delta_y) = if dy != 0.0 {
+            (boundary(y0, y, step_y) / dy, 1.0 / dy.abs())
+        } else {
+            (f64::INFINITY, f64::INFINITY)
+        };
+        self.insert(x, y, pixels);
+        for _ in 0..(end_x - x).abs() + (end_y - y).abs() {
+            if t_x < t_y {
+                t_x += delta_x;
+                x += step_x;
+            } else {
+                t_y += delta_y;
+                y += step_y;
+            }
+            self.insert(x, y, pixels);
+        }
+    }
+
+    fn burn_polygons(&self, polygons: &[Polygon], pixels: &mut BTreeSet<usize>) {
+        let coords = polygons
+            .iter()
+            .flat_map(|p| p.exterior().coords().map(|c| Coordinate::from(*c)));
+        let Some(bbox) = BoundingBox::from_coords(coords) else {
+            return;
+        };
+        let window = pixel_window(&bbox, self.origin, self.pixel_size, self.width, self.height);
+        for (row, start, end) in polygon_spans(polygons, self.origin, self.pixel_size, window) {
+            let offset = row as usize * self.width as usize;
+            pixels.extend((start..end).map(|col| offset + col as usize));
+        }
+        if self.all_touched {
+            let rings = polygons
+                .iter()
+                .flat_map(|p| std::iter::once(p.exterior()).chain(p.interiors()));
+            for ring in rings {
+                self.burn_line_string(ring, pixels);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::GeorasterError;
+    use geo_types::{line_string, point, polygon};
+
+    fn rasterizer() -> Rasterizer {
+        Rasterizer::new(6, 6, [0.0, 6.0], [1.0, -1.0])
+    }
+
+    fn burned(raster: &Raster) -> Vec<(u32, u32)> {
+        (0..raster.height)
+            .flat_map(|y| (0..raster.width).map(move |x| (x, y)))
+            .filter(|(x, y)| raster.get(*x, *y) != RasterValue::NoData)
+            .collect()
+    }
+
+    #[test]
+    fn points_and_lines() {
+        let points = Geometry::from(geo_types::MultiPoint::new(vec![
+            point! { x: 0.5, y: 5.5 },
+            point! { x: 3.0, y: 3.0 },
+            point! { x: 7.0, y: 3.0 },
+        ]));
+        let raster = rasterizer().rasterize([(&points, 2.0)]).unwrap();
+        assert_eq!(burned(&raster), [(0, 0), (3, 3)]);
+        assert_eq!(raster.get(3, 3), RasterValue::F64(2.0));
+
+        // Diagonal from outside the grid
+        let line = Geometry::from(line_string![(x: -1.0, y: 6.9), (x: 3.5, y: 2.4)]);
+        let raster = rasterizer().rasterize([(&line, 1.0)]).unwrap();
+        assert_eq!(burned(&raster), [(0, 0), (1, 1), (2, 2), (3, 3)]);
+
+        // Shallow line crossing pixel corners only on one side
+        let line = Geometry::from(line_string![(x: 0.5, y: 5.5), (x: 4.5, y: 4.5)]);
+        let raster = rasterizer().rasterize([(&line, 1.0)]).unwrap();
+        assert_eq!(burned(&raster), [(0, 0), (1, 0), (2, 1), (3, 1), (4, 1)]);
+        let raster = rasterizer()
+            .all_touched(true)
+            .rasterize([(&line, 1.0)])
+            .unwrap();
+        assert_eq!(
+            burned(&raster),
+            [(0, 0), (1, 0), (2, 0), (2, 1), (3, 1), (4, 1)]
+        );
+    }
+
+    #[test]
+    fn polygons() {
+        let square = Geometry::from(polygon![
+            (x: 1.2, y: 1.2),
+            (x: 3.8, y: 1.2),
+            (x: 3.8, y: 3.8),
+            (x: 1.2, y: 3.8),
+        ]);
+        let raster = rasterizer().rasterize([(&square, 5.0)]).unwrap();
+        assert_eq!(
+            burned(&raster),
+            [
+                (1, 2),
+                (2, 2),
+                (3, 2),
+                (1, 3),
+                (2, 3),
+                (3, 3),
+                (1, 4),
+                (2, 4),
+                (3, 4)
+            ]
+        );
+        let raster = rasterizer()
+            .all_touched(true)
+            .rasterize([(&square, 5.0)])
+            .unwrap();
+        assert_eq!(burned(&raster).len(), 9);
+        let raster = Rasterizer::new(6, 6, [0.0, 6.0], [1.0, -1.0])
+            .all_touched(true)
+            .rasterize([(
+                &Geometry::from(polygon![
+                    (x: 1.6, y: 1.6),
+                    (x: 3.4, y: 1.6),
+                    (x: 3.4, y: 3.4),
+                    (x: 1.6, y: 3.4),
+                ]),
+                1.0,
+            )])
+            .unwrap();
+        assert_eq!(burned(&raster).len(), 9);
+    }
+
+    #[test]
+    fn merge() {
+        let left = Geometry::from(polygon![
+            (x: 0.0, y: 0.0),
+            (x: 4.0, y: 0.0),
+            (x: 4.0, y: 6.0),
+            (x: 0.0, y: 6.0),
+        ]);
+        let right = Geometry::from(polygon![
+            (x: 2.0, y: 0.0),
+            (x: 6.0, y: 0.0),
+            (x: 6.0, y: 6.0),
+            (x: 2.0, y: 6.0),
+        ]);
+        let shapes = [(&left, 3.0), (&right, 2.0)];
+        let values = |rasterizer: Rasterizer| {
+            let raster = rasterizer
+                .sample_type(SampleType::U8)
+                .rasterize(shapes)
+                .unwrap();
+            [0, 3, 5].map(|x| raster.get(x, 0))
+        };
+        let v = RasterValue::U8;
+        assert_eq!(values(rasterizer()), [v(3), v(2), v(2)]);
+        assert_eq!(
+            values(rasterizer().merge(MergeAlg::Add)),
+            [v(3), v(5), v(2)]
+        );
+        assert_eq!(
+            values(rasterizer().merge(MergeAlg::Max)),
+            [v(3), v(3), v(2)]
+        );
+        // Added once per geometry, even with overlapping parts
+        let lines = Geometry::from(geo_types::MultiLineString::new(vec![
+            line_string![(x: 0.5, y: 0.5), (x: 0.5, y: 5.5)],
+            line_string![(x: 0.5, y: 0.5), (x: 5.5, y: 0.5)],
+        ]));
+        let raster = rasterizer()
+            .merge(MergeAlg::Add)
+            .fill(1.0)
+            .rasterize([(&lines, 1.0)])
+            .unwrap();
+        assert_eq!(raster.get(0, 5), RasterValue::F64(2.0));
+        assert_eq!(raster.get(3, 3), RasterValue::F64(1.0));
+
+        // Values which can't be represented in the sample type
+        let rasterizer = || rasterizer().sample_type(SampleType::U8);
+        let result = rasterizer()
+            .merge(MergeAlg::Add)
+            .rasterize([(&left, 200.0), (&right, 100.0)]);
+        assert!(matches!(result, Err(GeorasterError::ValueRange)));
+        let result = rasterizer().rasterize([(&left, -1.0)]);
+        assert!(matches!(result, Err(GeorasterError::ValueRange)));
+        let result = rasterizer().fill(0.5).rasterize([(&left, 1.0)]);
+        assert!(matches!(result, Err(GeorasterError::ValueRange)));
+    }
+}
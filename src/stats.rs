@@ -0,0 +1,256 @@
+//! Raster statistics.
+
+/// Band statistics
+#[derive(Debug, Clone, PartialEq)]
+pub struct Statistics {
+    pub min: f64,
+    pub max: f64,
+    pub mean: f64,
+    /// Population standard deviation
+    pub stddev: f64,
+    /// Number of valid values
+    pub valid_count: u64,
+    /// Number of NoData and NaN values
+    pub nodata_count: u64,
+}
+
+/// Streaming computation of `Statistics`
+#[derive(Debug, Clone)]
+pub(crate) struct StatisticsBuilder {
+    min: f64,
+    max: f64,
+    mean: f64,
+    m2: f64,
+    valid_count: u64,
+    nodata_count: u64,
+}
+
+impl Default for StatisticsBuilder {
+    fn default() -> Self {
+        StatisticsBuilder {
+            min: f64::INFINITY,
+            max: f64::NEG_INFINITY,
+            mean: 0.0,
+            m2: 0.0,
+            valid_count: 0,
+            nodata_count: 0,
+        }
+    }
+}
+
+impl StatisticsBuilder {
+    /// Add a value. `None` and NaN are counted as NoData.
+    pub fn add(&mut self, value: Option<f64>) {
+        match value {
+            Some(v) if !v.is_nan() => {
+                // Welford's online algorithm
+                self.valid_count += 1;
+                let delta = v - self.mean;
+                self.mean += delta / self.valid_count as f64;
+                self.m2 += delta * (v - self.mean);
+                self.min = self.min.min(v);
+                self.max = self.max.max(v);
+            }
+            _ => self.nodata_count += 1,
+        }
+    }
+
+    pub fn build(&self) -> Statistics {
+        if self.valid_count == 0 {
+            return Statistics {
+                min: f64::NAN,
+                max: f64::NAN,
+                mean: f64::NAN,
+                stddev: f64::NAN,
+                valid_count: 0,
+                nodata_count: self.nodata_count,
+            };
+        }
+        Statistics {
+            min: self.min,
+            max: self.max,
+            mean: self.mean,
+            stddev: (self.m2 / self.valid_count as f64).sqrt(),
+            valid_count: self.valid_count,
+            nodata_count: self.nodata_count,
+        }
+    }
+}
+
+/// Histogram with equally sized bins
+#[derive(Debug, Clone, PartialEq)]
+pub struct Histogram {
+    /// Lower bound of first bin
+    pub min: f64,
+    /// Upper bound of last bin
+    pub max: f64,
+    /// Number of values per bin
+    pub counts: Vec<u64>,
+}
+
+impl Histogram {
+    /// Create an empty histogram with `bins` bins covering `min..=max`
+    pub fn new(min: f64, max: f64, bins: usize) -> Self {
+        Histogram {
+            min,
+            max,
+            counts: vec![0; bins],
+        }
+    }
+
+    /// Width of a bin
+    pub fn bin_width(&self) -> f64 {
+        (self.max - self.min) / self.counts.len() as f64
+    }
+
+    /// Value range of bin `idx`
+    pub fn bin_range(&self, idx: usize) -> (f64, f64) {
+        let width = self.bin_width();
+        (
+            self.min + idx as f64 * width,
+            self.min + (idx + 1) as f64 * width,
+        )
+    }
+
+    /// Count value. Values outside of the histogram range are ignored.
+    pub fn add(&mut self, value: f64) {
+        if !(self.min..=self.max).contains(&value) || self.counts.is_empty() {
+            return;
+        }
+        let bins = self.counts.len();
+        let idx = if self.max > self.min {
+            (((value - self.min) / (self.max - self.min)) * bins as f64) as usize
+        } else {
+            0
+        };
+        // The maximum value belongs to the last bin
+        self.counts[idx.min(bins - 1)] += 1;
+    }
+}
+
+/// Statistics of the pixels within a zone
+#[derive(Debug, Clone, PartialEq)]
+pub struct ZonalStatistics {
+    /// Number of valid values
+    pub count: u64,
+    /// Number of NoData and NaN values
+    pub nodata_count: u64,
+    pub min: f64,
+    pub max: f64,
+    pub mean: f64,
+    pub sum: f64,
+    /// Sorted valid values
+    values: Vec<f64>,
+}
+
+impl ZonalStatistics {
+    /// Compute statistics of valid values. `None` and NaN are counted as NoData.
+    pub fn from_values(values: impl IntoIterator<Item = Option<f64>>) -> Self {
+        let mut nodata_count = 0;
+        let mut values = values
+            .into_iter()
+            .filter_map(|v| {
+                let v = v.filter(|v| !v.is_nan());
+                nodata_count += v.is_none() as u64;
+                v
+            })
+            .collect::<Vec<_>>();
+        values.sort_by(f64::total_cmp);
+        let sum = values.iter().sum::<f64>();
+        let count = values.len() as u64;
+        ZonalStatistics {
+            count,
+            nodata_count,
+            min: values.first().copied().unwrap_or(f64::NAN),
+            max: values.last().copied().unwrap_or(f64::NAN),
+            mean: if count > 0 {
+                sum / count as f64
+            } else {
+                f64::NAN
+            },
+            sum,
+            values,
+        }
+    }
+
+    /// Percentile `p` in `0.0..=100.0`, linearly interpolated between the closest ranks
+    ///
+    /// Returns NaN without valid values.
+    pub fn percentile(&self, p: f64) -> f64 {
+        if self.values.is_empty() {
+            return f64::NAN;
+        }
+        let rank = p.clamp(0.0, 100.0) / 100.0 * (self.values.len() - 1) as f64;
+        let (lower, upper) = (rank.floor() as usize, rank.ceil() as usize);
+        let t = rank - lower as f64;
+        self.values[lower] + t * (self.values[upper] - self.values[lower])
+    }
+
+    pub fn median(&self) -> f64 {
+        self.percentile(50.0)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn statistics() {
+        let mut builder = StatisticsBuilder::default();
+        for v in [2.0, 4.0, 4.0, 4.0, 5.0, 5.0, 7.0, 9.0] {
+            builder.add(Some(v));
+        }
+        builder.add(None);
+        builder.add(Some(f64::NAN));
+        assert_eq!(
+            builder.build(),
+            Statistics {
+                min: 2.0,
+                max: 9.0,
+                mean: 5.0,
+                stddev: 2.0,
+                valid_count: 8,
+                nodata_count: 2,
+            }
+        );
+
+        let stats = StatisticsBuilder::default().build();
+        assert!(stats.mean.is_nan());
+        assert_eq!(stats.valid_count, 0);
+    }
+
+    #[test]
+    fn histogram() {
+        let mut histogram = Histogram::new(0.0, 10.0, 4);
+        for v in [0.0, 2.4, 2.5, 5.0, 9.9, 10.0, 10.1, -1.0] {
+            histogram.add(v);
+        }
+        assert_eq!(histogram.counts, vec![2, 1, 1, 2]);
+        assert_eq!(histogram.bin_width(), 2.5);
+        assert_eq!(histogram.bin_range(1), (2.5, 5.0));
+    }
+
+    #[test]
+    fn zonal_statistics() {
+        let values = [
+            Some(4.0),
+            None,
+            Some(1.0),
+            Some(f64::NAN),
+            Some(3.0),
+            Some(2.0),
+        ];
+        let stats = ZonalStatistics::from_values(values);
+        assert_eq!((stats.count, stats.nodata_count), (4, 2));
+        assert_eq!((stats.min, stats.max), (1.0, 4.0));
+        assert_eq!((stats.sum, stats.mean), (10.0, 2.5));
+        assert_eq!(stats.median(), 2.5);
+        assert_eq!(stats.percentile(0.0), 1.0);
+        assert_eq!(stats.percentile(90.0), 3.7);
+
+        let stats = ZonalStatistics::from_values([None]);
+        assert_eq!(stats.count, 0);
+        assert!(stats.mean.is_nan() && stats.percentile(50.0).is_nan());
+    }
+}
@@ -0,0 +1,346 @@
+//! Terrain analysis of DEM rasters: slope, aspect and hillshade.
+
+use crate::geo;
+use crate::{Raster, RasterValue};
+use std::f64::consts::PI;
+
+/// Gradient kernel
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum Kernel {
+    /// Horn (1981) third-order finite difference over all 8 neighbours,
+    /// best suited for rough terrain
+    #[default]
+    Horn,
+    /// Zevenbergen & Thorne (1987) second-order finite difference over the
+    /// 4 direct neighbours, best suited for smooth terrain
+    ZevenbergenThorne,
+}
+
+/// Unit of slope values
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum SlopeUnit {
+    #[default]
+    Degrees,
+    Percent,
+}
+
+/// Hillshade illumination
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Hillshade {
+    /// Direction of the light source in degrees clockwise from north
+    pub azimuth: f64,
+    /// Elevation of the light source in degrees above the horizon
+    pub altitude: f64,
+    /// Combine light sources from 225°, 270°, 315° and 360° weighted by aspect,
+    /// ignoring `azimuth`
+    pub multidirectional: bool,
+}
+
+impl Default for Hillshade {
+    fn default() -> Self {
+        Hillshade {
+            azimuth: 315.0,
+            altitude: 45.0,
+            multidirectional: false,
+        }
+    }
+}
+
+/// Terrain analysis of a DEM raster
+///
+/// Heights are read with `RasterValue::as_f64`, PMTiles heights can be read
+/// with `PmtilesRasterReader::read_heights`. Results have the grid of the DEM.
+/// NoData pixels stay NoData. Missing neighbours at raster edges and next to
+/// NoData are extrapolated linearly from the other neighbours or replaced by
+/// the center height.
+///
+/// Pixel spacing is expected in metres, unless the CRS is set to geographic
+/// WGS84 (4326) or WebMercator (3857), which are converted into metres
+/// on the ground. Other geographic CRS are converted like WGS84 when set
+/// with `crs`.
+///
+/// ```rust
+/// use georaster::geotiff::GeoTiffReader;
+/// use georaster::terrain::{Hillshade, SlopeUnit, Terrain};
+/// use georaster::{BoundingBox, GeoRaster};
+/// use std::{fs::File, io::BufReader};
+///
+/// let img_file = BufReader::new(File::open("data/tiff/scaled_int16.tif").unwrap());
+/// let mut tiff = GeoTiffReader::open(img_file).unwrap();
+/// tiff.apply_scale_offset(true);
+/// let dem = tiff.read_extent(&tiff.extent().unwrap()).unwrap();
+/// let terrain = Terrain::new(&dem).crs(tiff.epsg_code(), tiff.is_geographic());
+/// let slope = terrain.slope(SlopeUnit::Degrees);
+/// let hillshade = terrain.hillshade(&Hillshade::default());
+/// assert_eq!((slope.width, slope.height), (dem.width, dem.height));
+/// ```
+#[derive(Debug, Clone)]
+pub struct Terrain<'a> {
+    dem: &'a Raster,
+    kernel: Kernel,
+    z_factor: f64,
+    epsg: Option<u32>,
+}
+
+impl<'a> Terrain<'a> {
+    pub fn new(dem: &'a Raster) -> Self {
+        Terrain {
+            dem,
+            kernel: Kernel::default(),
+            z_factor: 1.0,
+            epsg: None,
+        }
+    }
+
+    /// Set gradient kernel (default: Horn)
+    pub fn kernel(mut self, kernel: Kernel) -> Self {
+        self.kernel = kernel;
+        self
+    }
+
+    /// Set vertical exaggeration or conversion factor of heights into horizontal units (default: 1)
+    pub fn z_factor(mut self, z_factor: f64) -> Self {
+        self.z_factor = z_factor;
+        self
+    }
+
+    /// Set CRS of the DEM
+    pub fn epsg(mut self, epsg: u32) -> Self {
+        self.epsg = Some(epsg);
+        self
+    }
+
+    /// Set CRS of the DEM from the CRS of its source
+    ///
+    /// Any geographic CRS has its pixel spacing converted like WGS84, e.g. with
+    /// `GeoTiffReader::epsg_code` and `GeoTiffReader::is_geographic`.
+    pub fn crs(mut self, epsg: Option<u32>, geographic: bool) -> Self {
+        self.epsg = geo::ground_epsg(epsg, geographic);
+        self
+    }
+
+    /// Slope as `RasterValue::F32`
+    pub fn slope(&self, unit: SlopeUnit) -> Raster {
+        self.map(|(dzdx, dzdy)| {
+            let gradient = dzdx.hypot(dzdy);
+            let value = match unit {
+                SlopeUnit::Degrees => gradient.atan().to_degrees(),
+                SlopeUnit::Percent => gradient * 100.0,
+            };
+            RasterValue::F32(value as f32)
+        })
+    }
+
+    /// Downslope direction in degrees clockwise from north as `RasterValue::F32`
+    ///
+    /// Flat areas have no aspect and are returned as `RasterValue::NoData`.
+    pub fn aspect(&self) -> Raster {
+        self.map(|gradient| match aspect(gradient) {
+            Some(aspect) => RasterValue::F32(aspect.to_degrees() as f32),
+            None => RasterValue::NoData,
+        })
+    }
+
+    /// Hillshade as `RasterValue::U8` from 0 (shadow) to 255 (fully lit)
+    pub fn hillshade(&self, light: &Hillshade) -> Raster {
+        let zenith = (90.0 - light.altitude).to_radians();
+        let shade = move |slope: f64, aspect: f64, azimuth: f64| {
+            (zenith.cos() * slope.cos()
+                + zenith.sin() * slope.sin() * (azimuth.to_radians() - aspect).cos())
+            .max(0.0)
+        };
+        self.map(|gradient| {
+            let slope = gradient.0.hypot(gradient.1).atan();
+            // Flat areas are lit from the zenith
+            let aspect = aspect(gradient).unwrap_or(0.0);
+            let value = if light.multidirectional {
+                [225.0_f64, 270.0, 315.0, 360.0]
+                    .iter()
+                    .map(|azimuth| {
+                        let weight = (aspect - azimuth.to_radians()).sin().powi(2);
+                        weight * shade(slope, aspect, *azimuth)
+                    })
+                    .sum::<f64>()
+                    / 2.0
+            } else {
+                shade(slope, aspect, light.azimuth)
+            };
+            RasterValue::U8((value * 255.0).round() as u8)
+        })
+    }
+
+    /// Raster with `f` applied to the gradient `(dz/dx, dz/dy)` of all valid pixels
+    fn map(&self, f: impl Fn((f64, f64)) -> RasterValue) -> Raster {
+        let dem = self.dem;
+        let mut raster = Raster::new(dem.width, dem.height, dem.origin, dem.pixel_size);
+        for y in 0..dem.height {
+            let (spacing_x, spacing_y) = self.spacing(y);
+            for x in 0..dem.width {
+                if let Some(gradient) = self.gradient(x as i64, y as i64, spacing_x, spacing_y) {
+                    raster.set(x, y, f(gradient));
+                }
+            }
+        }
+        raster
+    }
+
+    /// Pixel width and height in metres of row `y`
+    fn spacing(&self, y: u32) -> (f64, f64) {
+        let dem = self.dem;
+        let center_y = dem.origin[1] + (y as f64 + 0.5) * dem.pixel_size[1];
+        let (scale_x, scale_y) = geo::ground_scale(self.epsg, center_y);
+        (
+            dem.pixel_size[0].abs() * scale_x,
+            dem.pixel_size[1].abs() * scale_y,
+        )
+    }
+
+    /// Height gradient towards east and north at pixel `(x, y)`
+    fn gradient(&self, x: i64, y: i64, spacing_x: f64, spacing_y: f64) -> Option<(f64, f64)> {
+        let height = |x: i64, y: i64| self.dem.pixel_at(x, y).as_f64().filter(|v| !v.is_nan());
+        let center = height(x, y)?;
+        // Neighbour at offset (dx, dy), extrapolated from the opposite neighbour if missing
+        let direct = |dx: i64, dy: i64| {
+            height(x + dx, y + dy)
+                .or_else(|| height(x - dx, y - dy).map(|opposite| 2.0 * center - opposite))
+        };
+        // Diagonal neighbours are extrapolated from the direct neighbours as last resort
+        let z = |dx: i64, dy: i64| {
+            direct(dx, dy).unwrap_or_else(|| {
+                let zx = direct(dx, 0).unwrap_or(center);
+                let zy = direct(0, dy).unwrap_or(center);
+                zx + zy - center
+            })
+        };
+        let (dzdx, dzdy) = match self.kernel {
+            Kernel::Horn => (
+                ((z(1, -1) + 2.0 * z(1, 0) + z(1, 1)) - (z(-1, -1) + 2.0 * z(-1, 0) + z(-1, 1)))
+                    / (8.0 * spacing_x),
+                ((z(-1, -1) + 2.0 * z(0, -1) + z(1, -1)) - (z(-1, 1) + 2.0 * z(0, 1) + z(1, 1)))
+                    / (8.0 * spacing_y),
+            ),
+            Kernel::ZevenbergenThorne => (
+                (z(1, 0) - z(-1, 0)) / (2.0 * spacing_x),
+                (z(0, -1) - z(0, 1)) / (2.0 * spacing_y),
+            ),
+        };
+        // Rows are ordered from north to south for north-up rasters
+        let north_up = if self.dem.pixel_size[1] < 0.0 {
+            1.0
+        } else {
+            -1.0
+        };
+        Some((dzdx * self.z_factor, dzdy * north_up * self.z_factor))
+    }
+}
+
+/// Downslope direction in radians clockwise from north, `None` for flat areas
+fn aspect((dzdx, dzdy): (f64, f64)) -> Option<f64> {
+    if dzdx == 0.0 && dzdy == 0.0 {
+        return None;
+    }
+    Some((-dzdx).atan2(-dzdy).rem_euclid(2.0 * PI))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::geo::EARTH_RADIUS;
+
+    /// DEM of an inclined plane with heights `a * x + b * y` in metres
+    fn plane(a: f64, b: f64, pixel_size: [f64; 2], origin: [f64; 2]) -> Raster {
+        let mut dem = Raster::new(5, 4, origin, pixel_size);
+        for y in 0..dem.height {
+            for x in 0..dem.width {
+                let c = dem.pixel_to_coord(x, y);
+                dem.set(x, y, RasterValue::F64(a * c.x + b * c.y));
+            }
+        }
+        dem
+    }
+
+    fn assert_all(raster: &Raster, expected: f64) {
+        for value in &raster.values {
+            let v = value.as_f64().unwrap();
+            assert!((v - expected).abs() < 1e-3, "{v} <> {expected}");
+        }
+    }
+
+    #[test]
+    fn slope_aspect() {
+        // Rising towards east
+        let dem = plane(0.1, 0.0, [10.0, -10.0], [1000.0, 2000.0]);
+        for kernel in [Kernel::Horn, Kernel::ZevenbergenThorne] {
+            let terrain = Terrain::new(&dem).kernel(kernel);
+            assert_all(&terrain.slope(SlopeUnit::Percent), 10.0);
+            assert_all(
+                &terrain.slope(SlopeUnit::Degrees),
+                0.1f64.atan().to_degrees(),
+            );
+            assert_all(&terrain.aspect(), 270.0);
+        }
+        let terrain = Terrain::new(&dem).z_factor(10.0);
+        assert_all(&terrain.slope(SlopeUnit::Degrees), 45.0);
+
+        // Rising towards north-east
+        let dem = plane(1.0, 1.0, [10.0, -10.0], [0.0, 0.0]);
+        assert_all(&Terrain::new(&dem).aspect(), 225.0);
+
+        // Flat with NoData
+        let mut dem = plane(0.0, 0.0, [10.0, -10.0], [0.0, 0.0]);
+        dem.set(2, 2, RasterValue::NoData);
+        let terrain = Terrain::new(&dem);
+        let slope = terrain.slope(SlopeUnit::Degrees);
+        assert_eq!(slope.get(2, 2), RasterValue::NoData);
+        assert_eq!(slope.get(2, 1), RasterValue::F32(0.0));
+        assert!(terrain
+            .aspect()
+            .values
+            .iter()
+            .all(|v| *v == RasterValue::NoData));
+    }
+
+    #[test]
+    fn geographic_spacing() {
+        // 0.001° pixels at 60° N, rising 10 m per pixel towards south
+        let metres = 0.001 * EARTH_RADIUS * PI / 180.0;
+        let dem = plane(0.0, -10.0 / 0.001, [0.001, -0.001], [8.0, 60.0]);
+        let terrain = Terrain::new(&dem).epsg(4326);
+        assert_all(&terrain.slope(SlopeUnit::Percent), 1000.0 / metres);
+        assert_all(&terrain.aspect(), 0.0);
+
+        // Rising 10 m per pixel towards east
+        let dem = plane(10.0 / 0.001, 0.0, [0.001, -0.001], [8.0, 60.001]);
+        let terrain = Terrain::new(&dem).epsg(4326);
+        let slope = terrain.slope(SlopeUnit::Percent);
+        let expected = 1000.0 / (metres * (60.0f64 - 0.0005).to_radians().cos());
+        assert!((slope.get(0, 0).as_f64().unwrap() - expected).abs() < 1e-3);
+    }
+
+    #[test]
+    fn hillshade() {
+        let flat = plane(0.0, 0.0, [10.0, -10.0], [0.0, 0.0]);
+        let terrain = Terrain::new(&flat);
+        // cos(45°) * 255
+        assert_all(&terrain.hillshade(&Hillshade::default()), 180.0);
+        let light = Hillshade {
+            multidirectional: true,
+            ..Default::default()
+        };
+        assert_all(&terrain.hillshade(&light), 180.0);
+
+        // Slope facing north-west towards the light source
+        let dem = plane(1.0, -1.0, [10.0, -10.0], [0.0, 0.0]);
+        let slope = 2.0f64.sqrt().atan();
+        let expected = (45f64.to_radians().cos() * slope.cos()
+            + 45f64.to_radians().sin() * slope.sin())
+            * 255.0;
+        assert_all(
+            &Terrain::new(&dem).hillshade(&Hillshade::default()),
+            expected.round(),
+        );
+        // Facing away from the light source
+        let dem = plane(-10.0, 10.0, [10.0, -10.0], [0.0, 0.0]);
+        assert_all(&Terrain::new(&dem).hillshade(&Hillshade::default()), 0.0);
+    }
+}
@@ -0,0 +1,262 @@
+//! Line of sight and viewshed analysis on DEM rasters.
+
+use crate::geo::{self, EARTH_RADIUS};
+use crate::{Coordinate, Raster, RasterValue};
+
+/// Observer and target setup
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Observer {
+    /// Eye height above the ground in metres
+    pub height: f64,
+    /// Height of the target above the ground in metres
+    pub target_height: f64,
+    /// Correct heights for the curvature of the earth
+    pub curvature: bool,
+    /// Coefficient of atmospheric refraction, reducing the curvature correction
+    pub refraction: f64,
+}
+
+impl Default for Observer {
+    fn default() -> Self {
+        Observer {
+            height: 1.7,
+            target_height: 0.0,
+            curvature: true,
+            refraction: 0.13,
+        }
+    }
+}
+
+impl Observer {
+    /// Apparent drop of the ground at ground distance `d` in metres
+    fn drop(&self, d: f64) -> f64 {
+        if self.curvature {
+            d * d / (2.0 * EARTH_RADIUS) * (1.0 - self.refraction)
+        } else {
+            0.0
+        }
+    }
+}
+
+/// Result of a line of sight computation
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct LineOfSight {
+    /// Target is visible from the observer
+    pub visible: bool,
+    /// Ground distance between observer and target
+    pub distance: f64,
+    /// Ground distance of the first obstacle from the observer
+    pub obstacle_distance: Option<f64>,
+}
+
+/// Line of sight between `from` and `to` in the CRS of `dem`
+///
+/// Heights are bilinear interpolated every half pixel, NoData samples are
+/// treated as transparent. Distances are in metres on the ground for
+/// geographic WGS84 (4326) and WebMercator (3857) DEMs, in CRS units otherwise.
+/// Returns `None` if the observer or target location has no height.
+pub fn line_of_sight(
+    dem: &Raster,
+    epsg: Option<u32>,
+    from: Coordinate,
+    to: Coordinate,
+    observer: &Observer,
+) -> Option<LineOfSight> {
+    let (scale_x, scale_y) = geo::ground_scale(epsg, (from.y + to.y) / 2.0);
+    let distance = ((to.x - from.x) * scale_x).hypot((to.y - from.y) * scale_y);
+    let eye = dem.interpolate(from)? + observer.height;
+    let target = dem.interpolate(to)? + observer.target_height - observer.drop(distance);
+
+    let (fx0, fy0) = dem.pixel_pos(from);
+    let (fx1, fy1) = dem.pixel_pos(to);
+    let n = (2.0 * (fx1 - fx0).abs().max((fy1 - fy0).abs())).ceil() as usize;
+    let obstacle_distance = (1..n).find_map(|i| {
+        let t = i as f64 / n as f64;
+        let coord = Coordinate {
+            x: from.x + t * (to.x - from.x),
+            y: from.y + t * (to.y - from.y),
+        };
+        let height = dem.interpolate(coord)? - observer.drop(t * distance);
+        (height > eye + t * (target - eye)).then_some(t * distance)
+    });
+    Some(LineOfSight {
+        visible: obstacle_distance.is_none(),
+        distance,
+        obstacle_distance,
+    })
+}
+
+/// Visible area within `radius` around the observer location `center`
+///
+/// Returns a raster with the grid of `dem` with `RasterValue::U8` 1 for visible
+/// and 0 for hidden pixels. Pixels outside `radius` and without height are
+/// `RasterValue::NoData`. Rays are traced from the observer to every pixel on
+/// the border of the analysed area, taking the nearest pixel height at each step.
+/// Units of `radius` are the same as for `line_of_sight` distances.
+pub fn viewshed(
+    dem: &Raster,
+    epsg: Option<u32>,
+    center: Coordinate,
+    radius: f64,
+    observer: &Observer,
+) -> Raster {
+    let mut raster = Raster::new(dem.width, dem.height, dem.origin, dem.pixel_size);
+    let Some(ground) = dem.interpolate(center) else {
+        return raster;
+    };
+    let eye = ground + observer.height;
+    let (scale_x, scale_y) = geo::ground_scale(epsg, center.y);
+    let size_x = dem.pixel_size[0].abs() * scale_x;
+    let size_y = dem.pixel_size[1].abs() * scale_y;
+    let (cx, cy) = dem.pixel_pos(center);
+    let (rx, ry) = (radius / size_x, radius / size_y);
+
+    // Pixels on the border of the analysed area
+    let x0 = (cx - rx).floor().max(0.0) as i64;
+    let x1 = (cx + rx).ceil().min(dem.width as f64 - 1.0) as i64;
+    let y0 = (cy - ry).floor().max(0.0) as i64;
+    let y1 = (cy + ry).ceil().min(dem.height as f64 - 1.0) as i64;
+    let border = (x0..=x1)
+        .flat_map(|x| [(x, y0), (x, y1)])
+        .chain((y0..=y1).flat_map(|y| [(x0, y), (x1, y)]));
+
+    let mut visible = vec![None; dem.values.len()];
+    let (ox, oy) = (cx.round() as i64, cy.round() as i64);
+    if dem.pixel_at(ox, oy) != RasterValue::NoData {
+        visible[oy as usize * dem.width as usize + ox as usize] = Some(true);
+    }
+    for (bx, by) in border {
+        let (dx, dy) = (bx as f64 - cx, by as f64 - cy);
+        let n = dx.abs().max(dy.abs()).ceil() as usize;
+        let mut max_angle = f64::NEG_INFINITY;
+        for i in 1..=n {
+            let t = i as f64 / n as f64;
+            let (x, y) = ((cx + t * dx).round() as i64, (cy + t * dy).round() as i64);
+            if x < 0 || y < 0 || x >= dem.width as i64 || y >= dem.height as i64 {
+                break;
+            }
+            let d = (t * dx * size_x).hypot(t * dy * size_y);
+            if d > radius {
+                break;
+            }
+            let Some(height) = dem.pixel_at(x, y).as_f64().filter(|h| !h.is_nan()) else {
+                continue;
+            };
+            let height = height - observer.drop(d);
+            let angle = (height - eye) / d;
+            let target_angle = (height + observer.target_height - eye) / d;
+            let idx = y as usize * dem.width as usize + x as usize;
+            let seen = visible[idx].unwrap_or(false) || target_angle >= max_angle;
+            visible[idx] = Some(seen);
+            max_angle = max_angle.max(angle);
+        }
+    }
+    for (value, seen) in raster.values.iter_mut().zip(visible) {
+        if let Some(seen) = seen {
+            *value = RasterValue::U8(seen as u8);
+        }
+    }
+    raster
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Flat DEM of 100x100 pixels of 10 metres with a wall of 20 metres at x = 50
+    fn dem() -> Raster {
+        let mut raster = Raster::new(100, 100, [0.0, 1000.0], [10.0, -10.0]);
+        for y in 0..100 {
+            for x in 0..100 {
+                let height = if x == 50 { 20.0 } else { 0.0 };
+                raster.set(x, y, RasterValue::F32(height));
+            }
+        }
+        raster
+    }
+
+    #[test]
+    fn sight() {
+        let dem = dem();
+        let observer = Observer::default();
+        let coord = |x: f64, y: f64| Coordinate { x, y };
+
+        let los = line_of_sight(
+            &dem,
+            None,
+            coord(105.0, 505.0),
+            coord(405.0, 505.0),
+            &observer,
+        );
+        assert_eq!(
+            los,
+            Some(LineOfSight {
+                visible: true,
+                distance: 300.0,
+                obstacle_distance: None,
+            })
+        );
+        let los = line_of_sight(
+            &dem,
+            None,
+            coord(105.0, 505.0),
+            coord(905.0, 505.0),
+            &observer,
+        )
+        .unwrap();
+        assert!(!los.visible);
+        let obstacle = los.obstacle_distance.unwrap();
+        assert!((390.0..=400.0).contains(&obstacle), "{obstacle}");
+        // Target higher than the wall
+        let tower = Observer {
+            target_height: 200.0,
+            ..observer
+        };
+        let los = line_of_sight(&dem, None, coord(105.0, 505.0), coord(905.0, 505.0), &tower);
+        assert!(los.unwrap().visible);
+        // Outside of the DEM
+        let los = line_of_sight(
+            &dem,
+            None,
+            coord(105.0, 505.0),
+            coord(-50.0, 505.0),
+            &observer,
+        );
+        assert_eq!(los, None);
+
+        // Earth curvature over 100 km of flat ground
+        let mut flat = Raster::new(101, 1, [0.0, 1000.0], [1000.0, -1000.0]);
+        flat.values.fill(RasterValue::F32(0.0));
+        let ground = Observer {
+            height: 0.0,
+            ..observer
+        };
+        let (from, to) = (coord(500.0, 500.0), coord(100_500.0, 500.0));
+        let los = line_of_sight(&flat, None, from, to, &ground).unwrap();
+        assert!(!los.visible);
+        let plane = Observer {
+            curvature: false,
+            ..ground
+        };
+        let los = line_of_sight(&flat, None, from, to, &plane).unwrap();
+        assert!(los.visible);
+    }
+
+    #[test]
+    fn visible_area() {
+        let dem = dem();
+        let center = Coordinate { x: 205.0, y: 505.0 };
+        let raster = viewshed(&dem, None, center, 400.0, &Observer::default());
+        // Observer
+        assert_eq!(raster.get(20, 50), RasterValue::U8(1));
+        assert_eq!(raster.get(49, 50), RasterValue::U8(1));
+        // Wall is visible, but hides the area behind
+        assert_eq!(raster.get(50, 50), RasterValue::U8(1));
+        assert_eq!(raster.get(55, 50), RasterValue::U8(0));
+        assert_eq!(raster.get(20, 85), RasterValue::U8(1));
+        // Outside radius
+        assert_eq!(raster.get(20, 95), RasterValue::NoData);
+        assert_eq!(raster.get(60, 50), RasterValue::NoData);
+        assert_eq!(raster.get(0, 0), RasterValue::NoData);
+    }
+}
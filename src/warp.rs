@@ -0,0 +1,323 @@
+//! Raster reprojection into a target CRS and grid.
+
+use crate::crs::Transformer;
+use crate::geo::BoundingBox;
+use crate::{Coordinate, GeoRaster, GeorasterError, GeorasterResult, Raster, RasterValue};
+
+const DEFAULT_MAX_PIXELS: u64 = 4096 * 4096;
+
+/// Resampling method
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum Resampling {
+    /// Value of the nearest source pixel
+    #[default]
+    Nearest,
+    /// Bilinear interpolation of the 2x2 nearest source pixels
+    Bilinear,
+    /// Cubic convolution (Catmull-Rom) of the 4x4 nearest source pixels
+    Cubic,
+    /// Average of all source pixels with their center within the output pixel
+    Average,
+}
+
+/// Reprojection of a raster source into a target CRS and grid
+///
+/// Each output pixel is mapped back into the source CRS and resampled.
+/// NoData and masked source pixels are skipped, output pixels without
+/// valid source pixels are `RasterValue::NoData`.
+///
+/// ```rust,no_run
+/// use georaster::{geotiff::GeoTiffReader, warp::{Resampling, Warper}, BoundingBox};
+/// use std::{fs::File, io::BufReader};
+///
+/// let img_file = BufReader::new(File::open("dem.tif").unwrap());
+/// let mut tiff = GeoTiffReader::open(img_file).unwrap();
+/// let bbox = BoundingBox::new(958826.0, 5987771.0, 978393.0, 6007339.0);
+/// let tile = Warper::from_extent(3857, &bbox, 256, 256)
+///     .resampling(Resampling::Bilinear)
+///     .warp(&mut tiff)
+///     .unwrap();
+/// ```
+#[derive(Debug, Clone)]
+pub struct Warper {
+    epsg: u32,
+    width: u32,
+    height: u32,
+    origin: [f64; 2],
+    pixel_size: [f64; 2],
+    resampling: Resampling,
+    src_epsg: Option<u32>,
+    max_pixels: u64,
+}
+
+impl Warper {
+    /// Target grid in CRS `epsg` with upper left corner `origin`
+    pub fn new(epsg: u32, width: u32, height: u32, origin: [f64; 2], pixel_size: [f64; 2]) -> Self {
+        Warper {
+            epsg,
+            width,
+            height,
+            origin,
+            pixel_size,
+            resampling: Resampling::default(),
+            src_epsg: None,
+            max_pixels: DEFAULT_MAX_PIXELS,
+        }
+    }
+
+    /// North-up target grid covering `bbox` in CRS `epsg`
+    pub fn from_extent(epsg: u32, bbox: &BoundingBox, width: u32, height: u32) -> Self {
+        let pixel_size = [
+            (bbox.right - bbox.left) / width as f64,
+            (bbox.bottom - bbox.top) / height as f64,
+        ];
+        Warper::new(epsg, width, height, [bbox.left, bbox.top], pixel_size)
+    }
+
+    /// Set resampling method (default: nearest)
+    pub fn resampling(mut self, resampling: Resampling) -> Self {
+        self.resampling = resampling;
+        self
+    }
+
+    /// Set CRS of the source, overriding the CRS reported by the source
+    pub fn src_epsg(mut self, epsg: u32) -> Self {
+        self.src_epsg = Some(epsg);
+        self
+    }
+
+    /// Set the maximal number of source pixels read for warping
+    ///
+    /// Larger source windows return `GeorasterError::WindowTooLarge`.
+    /// The default is 4096 x 4096 pixels.
+    pub fn with_max_pixels(mut self, max_pixels: u64) -> Self {
+        self.max_pixels = max_pixels;
+        self
+    }
+
+    /// Warp `src` into the target grid
+    ///
+    /// The source is read from the overview closest to the output resolution.
+    pub fn warp(&self, src: &mut impl GeoRaster) -> GeorasterResult<Raster> {
+        let src_epsg = self
+            .src_epsg
+            .or(src.epsg_code())
+            .ok_or_else(|| GeorasterError::UnsupportedCrs("Unknown raster CRS".to_string()))?;
+        let transformer = Transformer::new(self.epsg, src_epsg)?;
+        let mut raster = Raster::new(self.width, self.height, self.origin, self.pixel_size);
+        let src_resolution = src.resolution().ok_or(GeorasterError::NoGeoreference)?;
+
+        // Output pixel corners and centers in source CRS
+        let point = |x: f64, y: f64| {
+            transformer
+                .transform((
+                    self.origin[0] + x * self.pixel_size[0],
+                    self.origin[1] + y * self.pixel_size[1],
+                ))
+                .ok()
+        };
+        let corners = (0..=self.height)
+            .flat_map(|y| (0..=self.width).map(move |x| (x, y)))
+            .map(|(x, y)| point(x as f64, y as f64))
+            .collect::<Vec<_>>();
+        let centers = (0..self.height)
+            .flat_map(|y| (0..self.width).map(move |x| (x, y)))
+            .map(|(x, y)| point(x as f64 + 0.5, y as f64 + 0.5))
+            .collect::<Vec<_>>();
+
+        // Source window with a margin for the resampling kernel
+        let Some(bbox) = footprint(corners.iter().chain(centers.iter())) else {
+            return Ok(raster);
+        };
+        let resolution = (bbox.width() / self.width as f64).min(bbox.height() / self.height as f64);
+        let margin = 2.0
+            * src_resolution[0]
+                .abs()
+                .max(src_resolution[1].abs())
+                .max(resolution);
+        let data = src.read_extent_at(&bbox.buffer(margin), resolution, self.max_pixels)?;
+        if data.width == 0 || data.height == 0 {
+            return Ok(raster);
+        }
+
+        let stride = self.width as usize + 1;
+        for y in 0..self.height {
+            for x in 0..self.width {
+                let idx = y as usize * self.width as usize + x as usize;
+                let Some(center) = centers[idx] else {
+                    continue;
+                };
+                let value = match self.resampling {
+                    Resampling::Nearest => nearest(&data, center),
+                    Resampling::Bilinear => bilinear(&data, center),
+                    Resampling::Cubic => cubic(&data, center),
+                    Resampling::Average => {
+                        let (x, y) = (x as usize, y as usize);
+                        let pixel = [
+                            corners[y * stride + x],
+                            corners[y * stride + x + 1],
+                            corners[(y + 1) * stride + x],
+                            corners[(y + 1) * stride + x + 1],
+                        ];
+                        footprint(pixel.iter())
+                            .map(|bbox| average(&data, &bbox))
+                            .unwrap_or(RasterValue::NoData)
+                    }
+                };
+                raster.set(x, y, value);
+            }
+        }
+        Ok(raster)
+    }
+}
+
+/// Bounding box of all valid coordinates
+fn footprint<'a>(coords: impl Iterator<Item = &'a Option<Coordinate>>) -> Option<BoundingBox> {
+    BoundingBox::from_coords(coords.flatten().copied())
+}
+
+/// Interpolated value with the sample type of the source values
+fn interpolated(data: &Raster, x: i64, y: i64, value: f64) -> RasterValue {
+    let reference = (0..2)
+        .flat_map(|dy| (0..2).map(move |dx| (dx, dy)))
+        .map(|(dx, dy)| data.pixel_at(x + dx, y + dy))
+        .find(|v| *v != RasterValue::NoData);
+    match reference.and_then(|v| v.sample_type()) {
+        Some(sample_type) => RasterValue::F64(value).cast_to(sample_type),
+        None => RasterValue::NoData,
+    }
+}
+
+fn nearest(data: &Raster, coord: Coordinate) -> RasterValue {
+    let (fx, fy) = data.pixel_pos(coord);
+    data.pixel_at(fx.round() as i64, fy.round() as i64)
+}
+
+fn bilinear(data: &Raster, coord: Coordinate) -> RasterValue {
+    let (fx, fy) = data.pixel_pos(coord);
+    match data.interpolate(coord) {
+        Some(value) => interpolated(data, fx.floor() as i64, fy.floor() as i64, value),
+        // NoData, multi-band and non-numeric values
+        None => nearest(data, coord),
+    }
+}
+
+/// Catmull-Rom weights for the 4 pixels around fraction `t`
+fn cubic_weights(t: f64) -> [f64; 4] {
+    let (t2, t3) = (t * t, t * t * t);
+    [
+        (-t3 + 2.0 * t2 - t) / 2.0,
+        (3.0 * t3 - 5.0 * t2 + 2.0) / 2.0,
+        (-3.0 * t3 + 4.0 * t2 + t) / 2.0,
+        (t3 - t2) / 2.0,
+    ]
+}
+
+/// Cubic convolution, falls back to bilinear next to NoData pixels
+fn cubic(data: &Raster, coord: Coordinate) -> RasterValue {
+    let (fx, fy) = data.pixel_pos(coord);
+    let (x0, y0) = (fx.floor() as i64, fy.floor() as i64);
+    let wx = cubic_weights(fx - x0 as f64);
+    let wy = cubic_weights(fy - y0 as f64);
+    let mut sum = 0.0;
+    for (j, wy) in wy.iter().enumerate() {
+        for (i, wx) in wx.iter().enumerate() {
+            match data.pixel_at(x0 + i as i64 - 1, y0 + j as i64 - 1).as_f64() {
+                Some(v) if !v.is_nan() => sum += v * wx * wy,
+                _ => return bilinear(data, coord),
+            }
+        }
+    }
+    interpolated(data, x0, y0, sum)
+}
+
+/// Average of pixels with center inside `bbox`, nearest pixel for small footprints
+fn average(data: &Raster, bbox: &BoundingBox) -> RasterValue {
+    let (xa, ya) = data.pixel_pos(Coordinate {
+        x: bbox.left,
+        y: bbox.top,
+    });
+    let (xb, yb) = data.pixel_pos(Coordinate {
+        x: bbox.right,
+        y: bbox.bottom,
+    });
+    let (x0, x1) = (xa.min(xb).ceil() as i64, xa.max(xb).floor() as i64);
+    let (y0, y1) = (ya.min(yb).ceil() as i64, ya.max(yb).floor() as i64);
+    let mut sum = 0.0;
+    let mut count = 0;
+    let mut sample_type = None;
+    for y in y0..=y1 {
+        for x in x0..=x1 {
+            let value = data.pixel_at(x, y);
+            match value.as_f64() {
+                Some(v) if !v.is_nan() => {
+                    sum += v;
+                    count += 1;
+                    sample_type = value.sample_type();
+                }
+                None if value != RasterValue::NoData => {
+                    return nearest(data, bbox.center());
+                }
+                _ => {}
+            }
+        }
+    }
+    match sample_type {
+        Some(sample_type) => RasterValue::F64(sum / count as f64).cast_to(sample_type),
+        None if x0 > x1 || y0 > y1 => nearest(data, bbox.center()),
+        None => RasterValue::NoData,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn source() -> Raster {
+        // 6x6 pixels with value 10 * y + x, pixel (1, 1) is NoData
+        let mut raster = Raster::new(6, 6, [0.0, 6.0], [1.0, -1.0]);
+        for y in 0..6 {
+            for x in 0..6 {
+                raster.set(x, y, RasterValue::F32((10 * y + x) as f32));
+            }
+        }
+        raster.set(1, 1, RasterValue::NoData);
+        raster
+    }
+
+    fn assert_value(value: RasterValue, expected: f64) {
+        let v = value.as_f64().unwrap();
+        assert!((v - expected).abs() < 1e-4, "{v} <> {expected}");
+    }
+
+    #[test]
+    fn resampling() {
+        let warp = |resampling, size| {
+            Warper::from_extent(4326, &BoundingBox::new(0.0, 0.0, 6.0, 6.0), size, size)
+                .src_epsg(4326)
+                .resampling(resampling)
+                .warp(&mut source())
+                .unwrap()
+        };
+        assert_eq!(warp(Resampling::Nearest, 6), source());
+
+        let raster = warp(Resampling::Bilinear, 12);
+        assert_value(raster.get(6, 6), 30.25);
+        // Renormalized without NoData pixel (1, 1)
+        assert_value(raster.get(3, 3), 121.0 / 7.0);
+
+        let raster = warp(Resampling::Cubic, 12);
+        assert_value(raster.get(8, 8), 41.25);
+        // Bilinear fallback next to NoData
+        assert_value(raster.get(3, 3), 121.0 / 7.0);
+
+        let raster = warp(Resampling::Average, 3);
+        assert_value(raster.get(0, 0), 11.0 / 3.0);
+        assert_eq!(raster.get(1, 1), RasterValue::F32(27.5));
+
+        // Source without CRS
+        assert!(Warper::new(4326, 1, 1, [0.0, 0.0], [1.0, 1.0])
+            .warp(&mut source())
+            .is_err());
+    }
+}
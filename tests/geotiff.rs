@@ -1,6 +1,10 @@
-use georaster::{geotiff::GeoTiffReader, Coordinate, RasterValue};
+use georaster::{
+    geotiff::GeoTiffReader, BoundingBox, Coordinate, GeoRaster, GeorasterError, RasterValue,
+};
 use std::fs::File;
-use std::io::BufReader;
+use std::io::{BufReader, Cursor, Read, Seek, SeekFrom};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Arc;
 use tiff::tags::PhotometricInterpretation;
 
 #[test]
@@ -293,7 +297,7 @@ fn small_world() {
 fn small_world_pct() {
     let img_file =
         BufReader::new(File::open("data/tiff/small_world_pct.tif").expect("Open image file"));
-    let tiff = GeoTiffReader::open(img_file).expect("Open Tiff");
+    let mut tiff = GeoTiffReader::open(img_file).expect("Open Tiff");
     let img = tiff.images().first().expect("Image info");
     assert_eq!(img.dimensions, Some((400, 200)));
     assert_eq!(img.colortype, None);
@@ -304,8 +308,9 @@ fn small_world_pct() {
         img.photometric_interpretation,
         Some(PhotometricInterpretation::RGBPalette)
     );
-    // Palette is not supported
-    // assert_eq!(tiff.read_pixel(0, 0), RasterValue::U8(107)); // UnsupportedError(InterpretationWithBits(RGBPalette, [8]))
+    // Color indices
+    assert_eq!(tiff.read_pixel(0, 0), RasterValue::U8(249));
+    assert_eq!(tiff.read_pixel(399, 199), RasterValue::U8(147));
 }
 
 #[test]
@@ -483,6 +488,19 @@ fn rgb_bands() {
 
     // convert -quiet data/tiff/sat_multiband.tif[0] -crop 1x1+124+9 txt:
     assert_eq!(tiff.read_pixel(124, 9), RasterValue::U8(18));
+
+    // Bands of planar tiles with a single tile per band match the RGB image
+    let img_file = BufReader::new(File::open("data/tiff/sat.tif").expect("Open image file"));
+    let mut rgb = GeoTiffReader::open(img_file).expect("Open Tiff");
+    for (x, y) in [(0, 0), (124, 9), (199, 199)] {
+        let RasterValue::Rgb8(r, g, b) = rgb.read_pixel(x, y) else {
+            panic!("RasterValue::Rgb8(_)")
+        };
+        for (band, value) in [(1, r), (2, g), (3, b)] {
+            tiff.select_raster_band(band).unwrap();
+            assert_eq!(tiff.read_pixel(x, y), RasterValue::U8(value), "band {band}");
+        }
+    }
 }
 
 #[test]
@@ -549,3 +567,1004 @@ fn incomplete_strips() {
         }
     }
 }
+
+#[test]
+fn complex_int16() {
+    let img_file = BufReader::new(File::open("data/tiff/cint16.tif").expect("Open image file"));
+    let mut tiff = GeoTiffReader::open(img_file).expect("Open Tiff");
+
+    let img = tiff.images().first().expect("Image info");
+    assert_eq!(img.dimensions, Some((4, 4)));
+    assert_eq!(
+        img.sample_format,
+        Some(tiff::tags::SampleFormat::Unknown(5))
+    );
+
+    assert_eq!(tiff.read_pixel(0, 0), RasterValue::CI16(3, 4));
+    assert_eq!(tiff.read_pixel(0, 0).amplitude(), 5.0);
+    assert_eq!(tiff.read_pixel(2, 1), RasterValue::CI16(15, -8));
+
+    let pixels: Vec<_> = tiff.pixels(2, 3, 2, 1).collect();
+    assert_eq!(
+        pixels,
+        vec![
+            (2, 3, RasterValue::CI16(35, -28)),
+            (3, 3, RasterValue::CI16(36, -29))
+        ]
+    );
+}
+
+#[test]
+fn complex_float32() {
+    // Big endian
+    let img_file = BufReader::new(File::open("data/tiff/cfloat32.tif").expect("Open image file"));
+    let mut tiff = GeoTiffReader::open(img_file).expect("Open Tiff");
+
+    let img = tiff.images().first().expect("Image info");
+    assert_eq!(img.dimensions, Some((4, 4)));
+    assert_eq!(img.colortype, Some(tiff::ColorType::Gray(64)));
+
+    assert_eq!(tiff.read_pixel(0, 0), RasterValue::CF32(0.5, -1.5));
+    assert_eq!(tiff.read_pixel(3, 2), RasterValue::CF32(23.5, -6.0));
+    assert_eq!(tiff.read_pixel(1, 0).phase(), (-3.0f64).atan2(1.5));
+}
+
+/// Uncompressed 2 x 1 pixel TIFF with complex int32 values `[(3, -4), (-70000, 123456)]`
+fn complex_int32_tiff(big_endian: bool) -> Cursor<Vec<u8>> {
+    let u16_bytes = |v: u16| match big_endian {
+        true => v.to_be_bytes(),
+        false => v.to_le_bytes(),
+    };
+    let u32_bytes = |v: u32| match big_endian {
+        true => v.to_be_bytes(),
+        false => v.to_le_bytes(),
+    };
+    let mut data = Vec::new();
+    data.extend(if big_endian { b"MM" } else { b"II" });
+    data.extend(u16_bytes(42));
+    data.extend(u32_bytes(24));
+    // Image data at offset 8
+    for v in [3, -4, -70000, 123456i32] {
+        data.extend(u32_bytes(v as u32));
+    }
+    // IFD at offset 24 with (tag, type, value) entries of SHORT (3) or LONG (4) type
+    let entries = [
+        (256, 3, 2),  // ImageWidth
+        (257, 3, 1),  // ImageLength
+        (258, 3, 64), // BitsPerSample
+        (259, 3, 1),  // Compression: none
+        (262, 3, 1),  // PhotometricInterpretation: BlackIsZero
+        (273, 4, 8),  // StripOffsets
+        (277, 3, 1),  // SamplesPerPixel
+        (278, 3, 1),  // RowsPerStrip
+        (279, 4, 16), // StripByteCounts
+        (339, 3, 5),  // SampleFormat: complex int
+    ];
+    data.extend(u16_bytes(entries.len() as u16));
+    for (tag, typ, value) in entries {
+        data.extend(u16_bytes(tag));
+        data.extend(u16_bytes(typ));
+        data.extend(u32_bytes(1));
+        if typ == 3 {
+            data.extend(u16_bytes(value as u16));
+            data.extend([0, 0]);
+        } else {
+            data.extend(u32_bytes(value));
+        }
+    }
+    data.extend(u32_bytes(0));
+    Cursor::new(data)
+}
+
+#[test]
+fn complex_int32() {
+    for big_endian in [false, true] {
+        let mut tiff = GeoTiffReader::open(complex_int32_tiff(big_endian)).expect("Open Tiff");
+        let img = tiff.images().first().expect("Image info");
+        assert_eq!(img.colortype, Some(tiff::ColorType::Gray(64)));
+
+        assert_eq!(tiff.read_pixel(0, 0), RasterValue::CI32(3, -4));
+        assert_eq!(tiff.read_pixel(0, 0).amplitude(), 5.0);
+        assert_eq!(tiff.read_pixel(1, 0), RasterValue::CI32(-70000, 123456));
+    }
+}
+
+#[test]
+fn gdal_metadata() {
+    let img_file =
+        BufReader::new(File::open("data/tiff/scaled_int16.tif").expect("Open image file"));
+    let mut tiff = GeoTiffReader::open(img_file).expect("Open Tiff");
+
+    assert_eq!(tiff.nodata(), Some(-32768.0));
+    let metadata = tiff.metadata().expect("GDAL metadata");
+    assert_eq!(metadata.items["AREA_OR_POINT"], "Area");
+    assert_eq!(metadata.items["TITLE"], "Surface temperature & more");
+    let band = metadata.band(1).expect("Band metadata");
+    assert_eq!(band.scale, Some(0.01));
+    assert_eq!(band.offset, Some(-273.15));
+    assert_eq!(band.unit_type.as_deref(), Some("deg C"));
+    assert_eq!(band.description.as_deref(), Some("Temperature"));
+    assert_eq!(band.statistics.minimum, Some(27315.0));
+    assert_eq!(band.statistics.valid_percent, Some(93.75));
+    assert!(tiff.image_info().gdal_metadata.is_some());
+
+    // Raw values
+    assert_eq!(tiff.read_pixel(0, 0), RasterValue::I16(27315));
+    assert_eq!(tiff.read_pixel(1, 1), RasterValue::I16(-32768));
+
+    // Physical values
+    tiff.apply_scale_offset(true);
+    // Parses to the same f64 as the shorter literal suggested by clippy
+    #[allow(clippy::excessive_precision)]
+    let expected = RasterValue::F64(2.0000000000000568);
+    assert_eq!(tiff.read_pixel(2, 0), expected);
+    assert_eq!(tiff.read_pixel(1, 1), RasterValue::NoData);
+    let pixels: Vec<_> = tiff
+        .pixels(0, 1, 3, 1)
+        .map(|(_x, _y, v)| v.as_f64().map(|v| (v * 100.).round() / 100.))
+        .collect();
+    assert_eq!(pixels, vec![Some(5.0), None, Some(7.0)]);
+}
+
+#[test]
+fn statistics() {
+    let img_file = BufReader::new(File::open("data/tiff/byte.tif").expect("Open image file"));
+    let mut tiff = GeoTiffReader::open(img_file).expect("Open Tiff");
+
+    let stats = tiff.statistics(1, false).expect("Statistics");
+    assert_eq!((stats.min, stats.max), (74.0, 255.0));
+    assert_eq!((stats.mean * 1000.).round() / 1000., 126.765);
+    assert_eq!((stats.stddev * 1000.).round() / 1000., 22.928);
+    assert_eq!((stats.valid_count, stats.nodata_count), (400, 0));
+    assert!(tiff.statistics(2, false).is_err());
+
+    let histogram = tiff.histogram(1, 4, None, false).expect("Histogram");
+    assert_eq!(histogram.counts, vec![163, 205, 30, 2]);
+    let histogram = tiff.histogram(1, 2, Some((0.0, 100.0)), false).unwrap();
+    assert_eq!(histogram.counts.iter().sum::<u64>(), 44);
+
+    // Planar configuration
+    let img_file =
+        BufReader::new(File::open("data/tiff/sat_multiband.tif").expect("Open image file"));
+    let mut tiff = GeoTiffReader::open(img_file).expect("Open Tiff");
+    let stats = tiff.statistics(2, false).expect("Statistics");
+    assert_eq!(
+        (stats.min, stats.max, stats.valid_count),
+        (0.0, 215.0, 40000)
+    );
+}
+
+#[test]
+fn statistics_overview() {
+    let img_file =
+        BufReader::new(File::open("data/tiff/byte_overview.tif").expect("Open image file"));
+    let mut tiff = GeoTiffReader::open(img_file).expect("Open Tiff");
+    assert_eq!(tiff.images().len(), 3);
+    assert!(tiff.images()[2].is_mask());
+    tiff.seek_to_image(0).unwrap();
+
+    let stats = tiff.statistics(1, false).expect("Statistics");
+    assert_eq!((stats.min, stats.max), (0.0, 63.0));
+    assert_eq!((stats.valid_count, stats.nodata_count), (63, 1));
+
+    // Smallest overview, which has no mask
+    let stats = tiff.statistics(1, true).expect("Statistics");
+    assert_eq!((stats.min, stats.max), (6.0, 58.0));
+    assert_eq!((stats.valid_count, stats.nodata_count), (15, 1));
+
+    // Selected image is unchanged
+    assert_eq!(tiff.image_info().dimensions, Some((8, 8)));
+    assert_eq!(tiff.read_pixel(1, 0), RasterValue::U8(1));
+}
+
+#[test]
+fn statistics_mask() {
+    for file in ["byte_overview.tif", "byte_mask.tif"] {
+        let img_file =
+            BufReader::new(File::open(format!("data/tiff/{file}")).expect("Open image file"));
+        let mut tiff = GeoTiffReader::open(img_file).expect("Open Tiff");
+        let images = tiff.images().len();
+        // Valid pixels of full resolution image and smallest overview
+        for (approx, image) in [(false, 0), (true, images - 2)] {
+            let stats = tiff.statistics(1, approx).expect("Statistics");
+            tiff.seek_to_image(image).expect("Seek to image");
+            let raster = tiff
+                .read_extent(&tiff.extent().unwrap())
+                .expect("Read extent");
+            let valid = raster
+                .values
+                .iter()
+                .filter(|v| **v != RasterValue::NoData)
+                .count();
+            assert_eq!(stats.valid_count, valid as u64, "{file}");
+            assert_eq!(stats.nodata_count, (raster.values.len() - valid) as u64);
+        }
+    }
+    // Masked row
+    let img_file = BufReader::new(File::open("data/tiff/byte_mask.tif").expect("Open image file"));
+    let mut tiff = GeoTiffReader::open(img_file).expect("Open Tiff");
+    let stats = tiff.statistics(1, false).expect("Statistics");
+    assert_eq!((stats.valid_count, stats.nodata_count), (55, 9));
+    assert_eq!(stats.max, 55.0);
+    let histogram = tiff.histogram(1, 8, Some((0.0, 64.0)), false).unwrap();
+    assert_eq!(histogram.counts[7], 0);
+}
+
+#[test]
+fn epsg_code() {
+    let img_file = BufReader::new(File::open("data/tiff/utm.tif").expect("Open image file"));
+    let tiff = GeoTiffReader::open(img_file).expect("Open Tiff");
+    assert_eq!(tiff.epsg_code(), Some(26711));
+
+    let img_file =
+        BufReader::new(File::open("data/tiff/scaled_int16.tif").expect("Open image file"));
+    let tiff = GeoTiffReader::open(img_file).expect("Open Tiff");
+    assert_eq!(tiff.epsg_code(), Some(4326));
+}
+
+#[test]
+fn read_window() {
+    let img_file = BufReader::new(File::open("data/tiff/byte.tif").expect("Open image file"));
+    let mut tiff = GeoTiffReader::open(img_file).expect("Open Tiff");
+
+    let window = tiff.read_window(18, 5, 4, 2);
+    assert_eq!(window.len(), 8);
+    assert_eq!(window[0], tiff.read_pixel(18, 5));
+    assert_eq!(window[5], tiff.read_pixel(19, 6));
+    // Outside of image
+    assert_eq!(window[2], RasterValue::NoData);
+    assert_eq!(window[7], RasterValue::NoData);
+}
+
+/// Value at location of any raster source
+fn sample_at<R: GeoRaster>(raster: &mut R, coord: Coordinate) -> RasterValue {
+    raster.sample(coord).expect("Sample raster")
+}
+
+#[test]
+fn geo_raster() {
+    let img_file =
+        BufReader::new(File::open("data/tiff/scaled_int16.tif").expect("Open image file"));
+    let mut tiff = GeoTiffReader::open(img_file).expect("Open Tiff");
+
+    assert_eq!(
+        GeoRaster::extent(&tiff),
+        Some(BoundingBox::new(7.0, 45.0, 9.0, 47.0).with_epsg(4326))
+    );
+    assert_eq!(GeoRaster::epsg_code(&tiff), Some(4326));
+    assert_eq!(tiff.resolution(), Some([0.5, 0.5]));
+    assert_eq!(tiff.band_count(), 1);
+    assert_eq!(
+        sample_at(&mut tiff, Coordinate { x: 8.25, y: 46.75 }),
+        tiff.read_pixel(2, 0)
+    );
+    assert_eq!(
+        sample_at(&mut tiff, Coordinate { x: 6.9, y: 46.75 }),
+        RasterValue::NoData
+    );
+    // Lower right quarter of pixel (2, 0)
+    let location = Coordinate { x: 8.4, y: 46.6 };
+    assert_eq!(tiff.coord_to_pixel(location), Some((2, 0)));
+    assert_eq!(tiff.read_pixel_at_location(location), tiff.read_pixel(2, 0));
+    assert_eq!(sample_at(&mut tiff, location), tiff.read_pixel(2, 0));
+    // Just outside of the left and bottom edge
+    for location in [
+        Coordinate { x: 6.99, y: 46.75 },
+        Coordinate { x: 7.25, y: 44.99 },
+    ] {
+        assert_eq!(tiff.coord_to_pixel(location), None);
+        assert_eq!(tiff.read_pixel_at_location(location), RasterValue::NoData);
+        assert_eq!(sample_at(&mut tiff, location), RasterValue::NoData);
+    }
+
+    let mut raster = tiff
+        .read_extent(&BoundingBox::new(7.6, 45.0, 8.6, 46.4))
+        .expect("Read extent");
+    assert_eq!((raster.width, raster.height), (3, 3));
+    assert_eq!(raster.origin, [7.5, 46.5]);
+    // Pixel (1, 1) has the NoData value, which `read_extent` returns as NoData
+    assert_eq!(raster.get(1, 0), tiff.read_pixel(2, 1));
+    assert_eq!(raster.get(2, 2), tiff.read_pixel(3, 3));
+    // NoData value
+    assert_eq!(tiff.read_pixel(1, 1), RasterValue::I16(-32768));
+    assert_eq!(raster.get(0, 0), RasterValue::NoData);
+    // In-memory raster as source
+    assert_eq!(
+        sample_at(&mut raster, Coordinate { x: 8.25, y: 46.25 }),
+        tiff.read_pixel(2, 1)
+    );
+}
+
+/// Scaled 2 x 1 pixel GeoTIFF with values `[100, 0]`, NoData 0 and offset -100
+fn scaled_nodata_tiff() -> Cursor<Vec<u8>> {
+    use tiff::encoder::{colortype, TiffEncoder};
+    use tiff::tags::Tag;
+
+    let mut data = Cursor::new(Vec::new());
+    let mut encoder = TiffEncoder::new(&mut data).unwrap();
+    let mut image = encoder.new_image::<colortype::Gray8>(2, 1).unwrap();
+    let directory = image.encoder();
+    directory
+        .write_tag(Tag::ModelPixelScaleTag, &[1.0, 1.0, 0.0][..])
+        .unwrap();
+    directory
+        .write_tag(Tag::ModelTiepointTag, &[0.0, 0.0, 0.0, 0.0, 1.0, 0.0][..])
+        .unwrap();
+    directory.write_tag(Tag::GdalNodata, "0").unwrap();
+    let metadata =
+        r#"<GDALMetadata><Item name="OFFSET" sample="0" role="offset">-100</Item></GDALMetadata>"#;
+    directory.write_tag(Tag::Unknown(42112), metadata).unwrap();
+    image.write_data(&[100u8, 0][..]).unwrap();
+    data.set_position(0);
+    data
+}
+
+#[test]
+fn geo_raster_scaled_nodata() {
+    let mut tiff = GeoTiffReader::open(scaled_nodata_tiff()).expect("Open Tiff");
+    tiff.apply_scale_offset(true);
+    // Physical value 0 of pixel (0, 0) equals the raw NoData value
+    assert_eq!(tiff.read_pixel(0, 0), RasterValue::F64(0.0));
+    assert_eq!(tiff.read_pixel(1, 0), RasterValue::NoData);
+    assert_eq!(
+        sample_at(&mut tiff, Coordinate { x: 0.5, y: 0.5 }),
+        RasterValue::F64(0.0)
+    );
+    let raster = tiff
+        .read_extent(&tiff.extent().unwrap())
+        .expect("Read extent");
+    assert_eq!(raster.values, [RasterValue::F64(0.0), RasterValue::NoData]);
+
+    // Raw values
+    tiff.apply_scale_offset(false);
+    assert_eq!(
+        sample_at(&mut tiff, Coordinate { x: 0.5, y: 0.5 }),
+        RasterValue::U8(100)
+    );
+    assert_eq!(
+        sample_at(&mut tiff, Coordinate { x: 1.5, y: 0.5 }),
+        RasterValue::NoData
+    );
+}
+
+#[test]
+fn geo_raster_mask() {
+    let img_file = BufReader::new(File::open("data/tiff/byte_mask.tif").expect("Open image file"));
+    let mut tiff = GeoTiffReader::open(img_file).expect("Open Tiff");
+    assert_eq!(tiff.nodata(), Some(255.0));
+    assert!(tiff.images()[1].is_mask());
+    tiff.seek_to_image(0).expect("Seek to image");
+
+    let raster = tiff
+        .read_extent(&BoundingBox::new(7.0, 39.0, 15.0, 47.0))
+        .expect("Read extent");
+    assert_eq!((raster.width, raster.height), (8, 8));
+    assert_eq!(raster.get(0, 1), RasterValue::U8(8));
+    // NoData value
+    assert_eq!(tiff.read_pixel(1, 1), RasterValue::U8(255));
+    assert_eq!(raster.get(1, 1), RasterValue::NoData);
+    // Masked row
+    assert_eq!(tiff.read_pixel(3, 7), RasterValue::U8(59));
+    assert_eq!(raster.get(3, 7), RasterValue::NoData);
+    assert_eq!(
+        sample_at(&mut tiff, Coordinate { x: 10.5, y: 39.5 }),
+        RasterValue::NoData
+    );
+    assert_eq!(
+        sample_at(&mut tiff, Coordinate { x: 10.5, y: 40.5 }),
+        RasterValue::U8(51)
+    );
+}
+
+/// Reader counting read calls
+struct CountingReader<R> {
+    inner: R,
+    reads: Arc<AtomicUsize>,
+}
+
+impl<R: Read> Read for CountingReader<R> {
+    fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+        self.reads.fetch_add(1, Ordering::SeqCst);
+        self.inner.read(buf)
+    }
+}
+
+impl<R: Seek> Seek for CountingReader<R> {
+    fn seek(&mut self, pos: SeekFrom) -> std::io::Result<u64> {
+        self.inner.seek(pos)
+    }
+}
+
+#[test]
+fn geo_raster_mask_cache() {
+    let reads = Arc::new(AtomicUsize::new(0));
+    let img_file = CountingReader {
+        inner: File::open("data/tiff/byte_mask.tif").expect("Open image file"),
+        reads: reads.clone(),
+    };
+    let mut tiff = GeoTiffReader::open(img_file).expect("Open Tiff");
+    tiff.seek_to_image(0).expect("Seek to image");
+    let location = Coordinate { x: 10.5, y: 39.5 };
+
+    let count = reads.load(Ordering::SeqCst);
+    assert_eq!(sample_at(&mut tiff, location), RasterValue::NoData);
+    let first = reads.load(Ordering::SeqCst) - count;
+
+    // Decoded mask chunk is reused, only the image chunk is read again
+    let count = reads.load(Ordering::SeqCst);
+    assert_eq!(sample_at(&mut tiff, location), RasterValue::NoData);
+    let second = reads.load(Ordering::SeqCst) - count;
+    assert!(second < first, "{second} >= {first}");
+    let count = reads.load(Ordering::SeqCst);
+    tiff.read_pixel(3, 7);
+    assert_eq!(reads.load(Ordering::SeqCst) - count, second);
+}
+
+#[test]
+fn geo_raster_overview() {
+    let img_file =
+        BufReader::new(File::open("data/tiff/byte_overview.tif").expect("Open image file"));
+    let mut tiff = GeoTiffReader::open(img_file).expect("Open Tiff");
+    let extent = Some(BoundingBox::new(7.0, 39.0, 15.0, 47.0).with_epsg(4326));
+    tiff.seek_to_image(0).expect("Seek to image");
+    assert_eq!(GeoRaster::extent(&tiff), extent);
+    assert_eq!(tiff.resolution(), Some([1.0, 1.0]));
+
+    // 4 x 4 overview
+    tiff.seek_to_image(1).expect("Seek to image");
+    assert_eq!(GeoRaster::extent(&tiff), extent);
+    assert_eq!(tiff.resolution(), Some([2.0, 2.0]));
+    assert_eq!(tiff.coord_to_pixel((10.5, 44.5)), Some((1, 1)));
+    assert_eq!(
+        tiff.pixel_to_coord(1, 1),
+        Some(Coordinate { x: 9.0, y: 45.0 })
+    );
+    let raster = tiff
+        .read_extent(&BoundingBox::new(9.0, 43.0, 13.0, 47.0))
+        .expect("Read extent");
+    assert_eq!((raster.width, raster.height), (2, 2));
+    assert_eq!(raster.origin, [9.0, 47.0]);
+    assert_eq!(raster.pixel_size, [2.0, -2.0]);
+    assert_eq!(raster.get(0, 1), tiff.read_pixel(1, 1));
+    assert_eq!(
+        sample_at(&mut tiff, Coordinate { x: 10.5, y: 44.5 }),
+        tiff.read_pixel(1, 1)
+    );
+}
+
+#[test]
+fn profile() {
+    use georaster::profile::ProfileStep;
+
+    let img_file =
+        BufReader::new(File::open("data/tiff/scaled_int16.tif").expect("Open image file"));
+    let mut tiff = GeoTiffReader::open(img_file).expect("Open Tiff");
+    assert!(tiff.is_geographic());
+    let value = |tiff: &mut GeoTiffReader<_>, x, y| tiff.read_pixel(x, y).as_f64().unwrap();
+
+    // Along pixel centers of the first row
+    let line = [
+        Coordinate { x: 7.25, y: 46.75 },
+        Coordinate { x: 8.75, y: 46.75 },
+    ];
+    let profile = tiff.profile(&line, ProfileStep::PixelCrossing).unwrap();
+    let xs = profile.points.iter().map(|p| p.coord.x).collect::<Vec<_>>();
+    assert_eq!(xs, [7.25, 7.5, 8.0, 8.5, 8.75]);
+    assert_eq!(profile.points[0].value, Some(value(&mut tiff, 0, 0)));
+    assert_eq!(
+        profile.points[1].value,
+        Some((value(&mut tiff, 0, 0) + value(&mut tiff, 1, 0)) / 2.0)
+    );
+    assert_eq!(profile.points[4].value, Some(value(&mut tiff, 3, 0)));
+    // 1.5° longitude at 46.75° N
+    assert!((profile.length() - 114_282.0).abs() < 1.0);
+    assert_eq!(
+        profile.ascent(),
+        value(&mut tiff, 3, 0) - value(&mut tiff, 0, 0)
+    );
+    assert_eq!(profile.descent(), 0.0);
+
+    // NoData pixel (1, 1)
+    let line = [
+        Coordinate { x: 7.75, y: 46.25 },
+        Coordinate { x: 7.75, y: 45.25 },
+    ];
+    let profile = tiff
+        .profile(&line, ProfileStep::Distance(20_000.0))
+        .unwrap();
+    assert_eq!(profile.points.len(), 7);
+    assert_eq!(profile.points[0].value, None);
+    assert_eq!(profile.points[6].value, Some(value(&mut tiff, 1, 3)));
+}
+
+#[test]
+fn viewshed() {
+    use georaster::viewshed::Observer;
+
+    let img_file =
+        BufReader::new(File::open("data/tiff/scaled_int16.tif").expect("Open image file"));
+    let mut tiff = GeoTiffReader::open(img_file).expect("Open Tiff");
+    let (from, to) = (
+        Coordinate { x: 7.25, y: 46.75 },
+        Coordinate { x: 8.75, y: 46.75 },
+    );
+    // Heights rising linearly along the first row
+    let flat = Observer {
+        curvature: false,
+        ..Default::default()
+    };
+    let los = tiff.line_of_sight(from, to, &flat).unwrap().unwrap();
+    assert!(los.visible);
+    assert!((los.distance - 114_282.0).abs() < 5.0, "{}", los.distance);
+    // Hidden behind the curvature of the earth
+    let los = tiff
+        .line_of_sight(from, to, &Observer::default())
+        .unwrap()
+        .unwrap();
+    assert!(!los.visible);
+    assert!(los.obstacle_distance.unwrap() < los.distance);
+    // NoData pixel (1, 1)
+    let los = tiff
+        .line_of_sight(from, Coordinate { x: 7.75, y: 46.25 }, &flat)
+        .unwrap();
+    assert_eq!(los, None);
+
+    let raster = tiff.viewshed(from, 60_000.0, &flat).unwrap();
+    assert_eq!((raster.width, raster.height), (4, 3));
+    assert_eq!(raster.origin, [7.0, 47.0]);
+    assert_eq!(raster.get(0, 0), RasterValue::U8(1));
+    assert_eq!(raster.get(1, 0), RasterValue::U8(1));
+    assert_eq!(raster.get(0, 1), RasterValue::U8(1));
+    assert_eq!(raster.get(1, 1), RasterValue::NoData);
+    // Outside radius
+    assert_eq!(raster.get(2, 0), RasterValue::NoData);
+}
+
+#[test]
+fn terrain() {
+    use georaster::terrain::{SlopeUnit, Terrain};
+
+    // Projected DEM with 60 m pixels
+    let img_file = BufReader::new(File::open("data/tiff/float32.tif").expect("Open image file"));
+    let mut tiff = GeoTiffReader::open(img_file).expect("Open Tiff");
+    let dem = tiff
+        .read_extent(&tiff.extent().unwrap())
+        .expect("Read extent");
+    let slope = Terrain::new(&dem)
+        .crs(tiff.epsg_code(), tiff.is_geographic())
+        .slope(SlopeUnit::Percent);
+    assert_eq!((slope.width, slope.height), (dem.width, dem.height));
+    // Horn gradient at pixel (1, 1)
+    let z = |x, y| dem.get(x, y).as_f64().unwrap();
+    let dzdx = (z(2, 0) + 2.0 * z(2, 1) + z(2, 2) - z(0, 0) - 2.0 * z(0, 1) - z(0, 2)) / 480.0;
+    let dzdy = (z(0, 0) + 2.0 * z(1, 0) + z(2, 0) - z(0, 2) - 2.0 * z(1, 2) - z(2, 2)) / 480.0;
+    let value = slope.get(1, 1).as_f64().unwrap();
+    assert!((value - 100.0 * dzdx.hypot(dzdy)).abs() < 1e-3, "{value}");
+
+    // Geographic DEM with 0.5° pixels, heights of a few metres over 40 km
+    let img_file =
+        BufReader::new(File::open("data/tiff/scaled_int16.tif").expect("Open image file"));
+    let mut tiff = GeoTiffReader::open(img_file).expect("Open Tiff");
+    tiff.apply_scale_offset(true);
+    let dem = tiff
+        .read_extent(&tiff.extent().unwrap())
+        .expect("Read extent");
+    let slope = Terrain::new(&dem)
+        .crs(tiff.epsg_code(), tiff.is_geographic())
+        .slope(SlopeUnit::Degrees);
+    assert_eq!(
+        slope,
+        Terrain::new(&dem).epsg(4326).slope(SlopeUnit::Degrees)
+    );
+    let value = slope.get(2, 2).as_f64().unwrap();
+    assert!(value > 0.0 && value < 0.01, "{value}");
+    // Degrees taken as metres
+    let value = Terrain::new(&dem).slope(SlopeUnit::Degrees).get(2, 2);
+    assert!(value.as_f64().unwrap() > 1.0, "{value}");
+}
+
+#[test]
+fn write_geotiff() {
+    use georaster::geotiff_writer::GeoTiffWriter;
+    use georaster::Raster;
+    use std::io::Cursor;
+
+    let img_file = BufReader::new(File::open("data/tiff/byte.tif").expect("Open image file"));
+    let mut tiff = GeoTiffReader::open(img_file).expect("Open Tiff");
+    let raster = tiff.read_extent(&tiff.extent().unwrap()).unwrap();
+    let mut data = Cursor::new(Vec::new());
+    GeoTiffWriter::new()
+        .epsg(tiff.epsg_code().unwrap())
+        .write(&raster, &mut data)
+        .unwrap();
+    data.set_position(0);
+    let mut written = GeoTiffReader::open(data).expect("Open written Tiff");
+    assert_eq!(written.epsg_code(), tiff.epsg_code());
+    assert_eq!(written.origin(), tiff.origin());
+    assert_eq!(written.pixel_size(), tiff.pixel_size());
+    assert_eq!(written.nodata(), None);
+    assert_eq!(written.read_window(0, 0, 20, 20), raster.values);
+
+    // Geographic CRS with NoData
+    let mut raster = Raster::new(3, 2, [7.0, 47.0], [0.5, -0.5]);
+    raster.set(0, 0, RasterValue::F32(1.5));
+    raster.set(2, 1, RasterValue::F32(-3.0));
+    let mut data = Cursor::new(Vec::new());
+    GeoTiffWriter::new()
+        .epsg(4326)
+        .nodata(-9999.0)
+        .write(&raster, &mut data)
+        .unwrap();
+    data.set_position(0);
+    let mut written = GeoTiffReader::open(data).expect("Open written Tiff");
+    assert_eq!(written.epsg_code(), Some(4326));
+    assert!(written.is_geographic());
+    assert_eq!(written.nodata(), Some(-9999.0));
+    assert_eq!(written.read_pixel(0, 0), RasterValue::F32(1.5));
+    assert_eq!(written.read_pixel(1, 0), RasterValue::F32(-9999.0));
+    assert_eq!(written.read_pixel(2, 1), RasterValue::F32(-3.0));
+    assert_eq!(
+        written.read_pixel_at_location(Coordinate { x: 8.2, y: 46.3 }),
+        RasterValue::F32(-3.0)
+    );
+
+    // Integer raster with NoData pixels requires a NoData value
+    let mut raster = Raster::new(2, 1, [0.0, 0.0], [1.0, -1.0]);
+    raster.set(0, 0, RasterValue::U8(1));
+    let result = GeoTiffWriter::new().write(&raster, Cursor::new(Vec::new()));
+    assert!(result.is_err());
+    // NoData value out of the U8 range
+    let result = GeoTiffWriter::new()
+        .nodata(-9999.0)
+        .write(&raster, Cursor::new(Vec::new()));
+    assert!(matches!(result, Err(GeorasterError::ValueRange)));
+
+    // Geographic ETRS89 and projected World Equidistant Cylindrical
+    for (epsg, geographic) in [(4258, true), (4087, false)] {
+        let mut data = Cursor::new(Vec::new());
+        GeoTiffWriter::new()
+            .crs(epsg, geographic)
+            .nodata(255.0)
+            .write(&raster, &mut data)
+            .unwrap();
+        data.set_position(0);
+        let written = GeoTiffReader::open(data).expect("Open written Tiff");
+        assert_eq!(written.epsg_code(), Some(epsg));
+        assert_eq!(written.is_geographic(), geographic);
+    }
+    let mut data = Cursor::new(Vec::new());
+    GeoTiffWriter::new()
+        .epsg(4087)
+        .nodata(255.0)
+        .write(&raster, &mut data)
+        .unwrap();
+    data.set_position(0);
+    let written = GeoTiffReader::open(data).expect("Open written Tiff");
+    assert!(!written.is_geographic());
+}
+
+#[cfg(feature = "geo")]
+#[test]
+fn zonal_stats() {
+    use geo_types::{polygon, LineString, MultiPolygon, Polygon};
+
+    let img_file = BufReader::new(File::open("data/tiff/byte.tif").expect("Open image file"));
+    let mut tiff = GeoTiffReader::open(img_file).expect("Open Tiff");
+    // Polygon covering the pixel centers of the window x0..x1, y0..y1
+    let rect = |tiff: &GeoTiffReader<_>, x0, y0, x1, y1| {
+        let ul = tiff.pixel_to_coord(x0, y0).unwrap();
+        let lr = tiff.pixel_to_coord(x1, y1).unwrap();
+        LineString::from(vec![(ul.x, ul.y), (lr.x, ul.y), (lr.x, lr.y), (ul.x, lr.y)])
+    };
+    let window_values = |tiff: &mut GeoTiffReader<_>, x0, y0, x1, y1| {
+        tiff.read_window(x0, y0, x1 - x0, y1 - y0)
+            .iter()
+            .map(|v| v.as_f64().unwrap())
+            .collect::<Vec<_>>()
+    };
+
+    let polygon = Polygon::new(rect(&tiff, 5, 2, 15, 12), vec![]);
+    let stats = tiff.zonal_stats(&polygon).unwrap();
+    let values = window_values(&mut tiff, 5, 2, 15, 12);
+    assert_eq!((stats.count, stats.nodata_count), (100, 0));
+    assert_eq!(stats.sum, values.iter().sum::<f64>());
+    assert_eq!(stats.min, values.iter().copied().fold(f64::MAX, f64::min));
+    assert_eq!(stats.max, values.iter().copied().fold(f64::MIN, f64::max));
+    assert_eq!(stats.mean, stats.sum / 100.0);
+    assert!(stats.min <= stats.median() && stats.median() <= stats.max);
+
+    // Polygon with hole
+    let polygon = Polygon::new(rect(&tiff, 5, 2, 15, 12), vec![rect(&tiff, 8, 8, 10, 10)]);
+    let stats = tiff.zonal_stats(&polygon).unwrap();
+    let hole = window_values(&mut tiff, 8, 8, 10, 10);
+    assert_eq!(stats.count, 96);
+    assert_eq!(
+        stats.sum,
+        values.iter().sum::<f64>() - hole.iter().sum::<f64>()
+    );
+
+    let polygons = MultiPolygon(vec![
+        Polygon::new(rect(&tiff, 0, 0, 2, 2), vec![]),
+        Polygon::new(rect(&tiff, 18, 17, 20, 20), vec![]),
+    ]);
+    let stats = tiff.zonal_stats_multi(&polygons).unwrap();
+    assert_eq!(stats.count, 10);
+    assert_eq!(
+        stats.sum,
+        window_values(&mut tiff, 0, 0, 2, 2).iter().sum::<f64>()
+            + window_values(&mut tiff, 18, 17, 20, 20).iter().sum::<f64>()
+    );
+
+    let outside = polygon![(x: 0.0, y: 0.0), (x: 1.0, y: 0.0), (x: 1.0, y: 1.0)];
+    assert_eq!(tiff.zonal_stats(&outside).unwrap().count, 0);
+
+    // NoData and masked pixels
+    let img_file = BufReader::new(File::open("data/tiff/byte_mask.tif").expect("Open image file"));
+    let mut tiff = GeoTiffReader::open(img_file).expect("Open Tiff");
+    tiff.seek_to_image(0).expect("Seek to image");
+    let polygon =
+        polygon![(x: 7.0, y: 39.0), (x: 9.0, y: 39.0), (x: 9.0, y: 47.0), (x: 7.0, y: 47.0)];
+    let stats = tiff.zonal_stats(&polygon).unwrap();
+    assert_eq!((stats.count, stats.nodata_count), (13, 3));
+    assert_eq!((stats.min, stats.max), (0.0, 49.0));
+}
+
+#[cfg(feature = "geo")]
+#[test]
+fn contours() {
+    use georaster::contour::ContourLevels;
+
+    let img_file =
+        BufReader::new(File::open("data/tiff/scaled_int16.tif").expect("Open image file"));
+    let mut tiff = GeoTiffReader::open(img_file).expect("Open Tiff");
+    let levels = ContourLevels::Interval {
+        base: 0.0,
+        interval: 50.0,
+    };
+    let contours = tiff.contours(0, 0, 4, 4, &levels).unwrap();
+    assert!(!contours.is_empty());
+    for contour in &contours {
+        assert_eq!(contour.elevation % 50.0, 0.0);
+        for c in &contour.line.0 {
+            assert!((7.25..=8.75).contains(&c.x) && (45.25..=46.75).contains(&c.y));
+        }
+    }
+
+    // Left and right half meet at the pixel centers of column 2, the upper
+    // left cells next to NoData pixel (1, 1) have no contours
+    let border = |contours: &[georaster::contour::Contour]| {
+        let mut coords = contours
+            .iter()
+            .flat_map(|c| c.line.0.iter().map(move |p| (c.elevation, p.x, p.y)))
+            .filter(|(_, x, _)| *x == 8.25)
+            .collect::<Vec<_>>();
+        coords.sort_by(|a, b| a.partial_cmp(b).unwrap());
+        coords
+    };
+    let left = tiff.contours(0, 0, 2, 4, &levels).unwrap();
+    let right = tiff.contours(2, 0, 2, 4, &levels).unwrap();
+    let (left, right) = (border(&left), border(&right));
+    assert_eq!(left.len(), 10);
+    assert!(left.iter().all(|c| right.contains(c)));
+}
+
+#[cfg(feature = "geo")]
+#[test]
+fn polygonize() {
+    use geo_types::LineString;
+    use georaster::polygonize::Connectivity;
+
+    // Twice the signed area
+    let area = |ring: &LineString| -> f64 { ring.lines().map(|l| l.determinant()).sum() };
+
+    let img_file =
+        BufReader::new(File::open("data/tiff/small_world_pct.tif").expect("Open image file"));
+    let mut tiff = GeoTiffReader::open(img_file).expect("Open Tiff");
+    let regions = tiff.polygonize(1, Connectivity::Four).unwrap();
+    assert_eq!(regions[0].value, RasterValue::U8(249));
+    // Regions cover the whole world
+    let total = regions
+        .iter()
+        .flat_map(|r| r.geometry.iter())
+        .map(|p| area(p.exterior()) + p.interiors().iter().map(area).sum::<f64>())
+        .sum::<f64>();
+    assert!((total / 2.0 - 360.0 * 180.0).abs() < 1e-6, "{total}");
+    assert!(regions
+        .iter()
+        .flat_map(|r| r.geometry.iter())
+        .all(|p| area(p.exterior()) > 0.0 && p.interiors().iter().all(|r| area(r) < 0.0)));
+    let eight = tiff.polygonize(1, Connectivity::Eight).unwrap();
+    assert!(eight.len() < regions.len());
+    assert!(tiff.polygonize(2, Connectivity::Four).is_err());
+
+    // NoData and masked pixels
+    let img_file = BufReader::new(File::open("data/tiff/byte_mask.tif").expect("Open image file"));
+    let mut tiff = GeoTiffReader::open(img_file).expect("Open Tiff");
+    tiff.seek_to_image(0).unwrap();
+    let regions = tiff.polygonize(1, Connectivity::Four).unwrap();
+    // 64 pixels without NoData pixel (1, 1) and masked row 7
+    assert_eq!(regions.len(), 55);
+    assert!(regions.iter().all(|r| r.value != RasterValue::U8(255)));
+}
+
+#[cfg(feature = "geo")]
+#[test]
+fn rasterize() {
+    use geo_types::{Geometry, LineString, Polygon};
+    use georaster::rasterize::{MergeAlg, Rasterizer};
+    use georaster::SampleType;
+
+    let img_file = BufReader::new(File::open("data/tiff/byte.tif").expect("Open image file"));
+    let mut tiff = GeoTiffReader::open(img_file).expect("Open Tiff");
+    let (width, height) = tiff.images()[0].dimensions.unwrap();
+    let rasterizer = Rasterizer::new(
+        width,
+        height,
+        tiff.origin().unwrap(),
+        tiff.pixel_size().unwrap(),
+    )
+    .sample_type(SampleType::U8);
+    let ring = |x0, y0, x1, y1| {
+        let ul = tiff.pixel_to_coord(x0, y0).unwrap();
+        let lr = tiff.pixel_to_coord(x1, y1).unwrap();
+        LineString::from(vec![(ul.x, ul.y), (lr.x, ul.y), (lr.x, lr.y), (ul.x, lr.y)])
+    };
+    let polygon = Polygon::new(ring(5, 2, 15, 12), vec![ring(8, 8, 10, 10)]);
+    let mask = rasterizer
+        .rasterize([(&Geometry::from(polygon.clone()), 1.0)])
+        .expect("Rasterize");
+    let burned = mask
+        .values
+        .iter()
+        .filter(|v| **v == RasterValue::U8(1))
+        .count();
+    // Mask pixels match the pixels of zonal statistics
+    let stats = tiff.zonal_stats(&polygon).unwrap();
+    assert_eq!(burned as u64, stats.count);
+    let sum = tiff
+        .read_window(0, 0, width, height)
+        .iter()
+        .zip(&mask.values)
+        .filter(|(_, m)| **m != RasterValue::NoData)
+        .map(|(v, _)| v.as_f64().unwrap())
+        .sum::<f64>();
+    assert_eq!(sum, stats.sum);
+
+    // Label counts of overlapping shapes
+    let outline = Geometry::from(polygon.exterior().clone());
+    let labels = rasterizer
+        .all_touched(true)
+        .merge(MergeAlg::Add)
+        .rasterize([(&Geometry::from(polygon), 1.0), (&outline, 1.0)])
+        .expect("Rasterize");
+    assert_eq!(labels.get(5, 2), RasterValue::U8(2));
+    assert_eq!(labels.get(10, 5), RasterValue::U8(1));
+    assert_eq!(labels.get(9, 9), RasterValue::NoData);
+    assert_eq!(labels.get(0, 0), RasterValue::NoData);
+}
+
+#[cfg(feature = "geodesy")]
+#[test]
+fn read_pixel_with_crs() {
+    use georaster::crs::Transformer;
+
+    let img_file = BufReader::new(File::open("data/tiff/byte.tif").expect("Open image file"));
+    let mut tiff = GeoTiffReader::open(img_file).expect("Open Tiff");
+    assert_eq!(tiff.epsg_code(), Some(26711));
+
+    let [origin_x, origin_y] = tiff.origin().unwrap();
+    let [pixel_size_x, pixel_size_y] = tiff.pixel_size().unwrap();
+    for (x, y) in [(10, 5), (0, 0), (17, 19)] {
+        let coord = Coordinate {
+            x: origin_x + (x as f64 + 0.25) * pixel_size_x,
+            y: origin_y + (y as f64 + 0.25) * pixel_size_y,
+        };
+        let lonlat = Transformer::new(26711, 4326)
+            .unwrap()
+            .transform(coord)
+            .unwrap();
+        assert!((-118.0..-117.0).contains(&lonlat.x) && (33.0..34.0).contains(&lonlat.y));
+        assert_eq!(
+            tiff.read_pixel_at_location_with_crs(lonlat, 4326).unwrap(),
+            tiff.read_pixel(x, y)
+        );
+    }
+    assert!(tiff
+        .read_pixel_at_location_with_crs((7.0, 47.0), 31467)
+        .is_err());
+}
+
+#[cfg(feature = "geodesy")]
+#[test]
+fn warp() {
+    use georaster::crs::Transformer;
+    use georaster::warp::{Resampling, Warper};
+    use georaster::SampleType;
+
+    let img_file = BufReader::new(File::open("data/tiff/byte_mask.tif").expect("Open image file"));
+    let mut tiff = GeoTiffReader::open(img_file).expect("Open Tiff");
+    tiff.seek_to_image(0).expect("Seek to image");
+
+    // WebMercator grid covering the raster
+    let t = Transformer::new(4326, 3857).unwrap();
+    let ll = t.transform((7.0, 39.0)).unwrap();
+    let ur = t.transform((15.0, 47.0)).unwrap();
+    let bbox = BoundingBox::new(ll.x, ll.y, ur.x, ur.y);
+    let raster = Warper::from_extent(3857, &bbox, 32, 32)
+        .warp(&mut tiff)
+        .expect("Warp");
+    assert_eq!((raster.width, raster.height), (32, 32));
+    let lonlat = Transformer::new(3857, 4326).unwrap();
+    for (x, y) in [(0, 0), (5, 4), (20, 13), (31, 24), (31, 31)] {
+        let center = raster.pixel_to_coord(x, y);
+        let center = Coordinate {
+            x: center.x + raster.pixel_size[0] / 2.0,
+            y: center.y + raster.pixel_size[1] / 2.0,
+        };
+        let coord = lonlat.transform(center).unwrap();
+        assert_eq!(raster.get(x, y), sample_at(&mut tiff, coord));
+    }
+    // NoData and masked pixels
+    assert_eq!(raster.get(5, 5), RasterValue::NoData);
+    assert_eq!(raster.get(10, 31), RasterValue::NoData);
+
+    let raster = Warper::from_extent(3857, &bbox, 4, 4)
+        .resampling(Resampling::Average)
+        .warp(&mut tiff)
+        .expect("Warp");
+    let value = raster.get(2, 1).as_f64().unwrap();
+    assert!((20.0..40.0).contains(&value), "{value}");
+    assert_eq!(raster.get(0, 3).sample_type(), Some(SampleType::U8));
+
+    let raster = Warper::from_extent(3857, &bbox, 16, 16)
+        .resampling(Resampling::Bilinear)
+        .warp(&mut tiff)
+        .expect("Warp");
+    assert!(matches!(raster.get(8, 8), RasterValue::U8(_)));
+    assert_eq!(raster.get(8, 15), RasterValue::NoData);
+}
+
+#[cfg(feature = "geodesy")]
+#[test]
+fn warp_overview() {
+    use georaster::warp::Warper;
+
+    let img_file =
+        BufReader::new(File::open("data/tiff/byte_overview.tif").expect("Open image file"));
+    let mut tiff = GeoTiffReader::open(img_file).expect("Open Tiff");
+    let bbox = BoundingBox::new(7.0, 39.0, 15.0, 47.0);
+
+    // 4x4 overview matches the output resolution
+    let raster = Warper::from_extent(4326, &bbox, 4, 4)
+        .src_epsg(4326)
+        .warp(&mut tiff)
+        .expect("Warp");
+    tiff.seek_to_image(1).expect("Seek to image");
+    let overview = tiff.read_extent(&bbox).expect("Read overview");
+    tiff.seek_to_image(0).expect("Seek to image");
+    assert_eq!(raster.values, overview.values);
+    assert_eq!(raster.get(1, 1), RasterValue::U8(22));
+    assert_eq!(tiff.image_info().dimensions, Some((8, 8)));
+
+    let raster = Warper::from_extent(4326, &bbox, 8, 8)
+        .src_epsg(4326)
+        .warp(&mut tiff)
+        .expect("Warp");
+    assert_eq!(raster.get(1, 1), RasterValue::NoData);
+    assert_eq!(raster.get(3, 3), RasterValue::U8(27));
+
+    let result = Warper::from_extent(4326, &bbox, 8, 8)
+        .src_epsg(4326)
+        .with_max_pixels(25)
+        .warp(&mut tiff);
+    assert!(matches!(
+        result,
+        Err(GeorasterError::WindowTooLarge(81, 25))
+    ));
+    let raster = Warper::from_extent(4326, &bbox, 4, 4)
+        .src_epsg(4326)
+        .with_max_pixels(25)
+        .warp(&mut tiff)
+        .expect("Warp");
+    assert_eq!(raster.values, overview.values);
+}